@@ -1,3 +1,5 @@
 pub mod download;
+pub mod drift;
+pub mod import_cookies;
 pub mod login;
 pub mod search;
@@ -4,6 +4,7 @@ use crate::util::sanitize_path;
 use anyhow::Context;
 use anyhow::bail;
 use anyhow::ensure;
+use deviantart::DeviationSource;
 use deviantart::Url;
 use std::fmt::Write as _;
 use std::io::Write;
@@ -41,18 +42,35 @@ pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Re
         .await?;
     }
 
-    let scraped_webpage_info = client
-        .scrape_webpage(&options.url)
+    download(&client, &client, &options).await
+}
+
+/// Download a deviation.
+///
+/// Deviation info comes from the `source`, while files are downloaded with the `client`.
+async fn download<S>(
+    source: &S,
+    client: &deviantart::Client,
+    options: &Options,
+) -> anyhow::Result<()>
+where
+    S: DeviationSource,
+{
+    let deviation_info = source
+        .get_deviation(&options.url)
         .await
-        .context("failed to scrape webpage")?;
-    let current_deviation = scraped_webpage_info
-        .get_current_deviation()
-        .context("failed to get current deviation")?;
-    let current_deviation_extended = scraped_webpage_info
-        .get_current_deviation_extended()
+        .context("failed to get deviation")?;
+    let current_deviation = &deviation_info.deviation;
+    let current_deviation_extended = deviation_info
+        .deviation_extended
+        .as_ref()
         .context("failed to get current deviation extended")?;
 
     println!("Title: {}", current_deviation.title);
+    println!(
+        "Author: {}",
+        current_deviation.author_username().unwrap_or("(unknown)")
+    );
     println!("ID: {}", current_deviation.deviation_id);
     println!("Kind: {}", current_deviation.kind);
     println!("Url: {}", current_deviation.url);
@@ -77,15 +95,15 @@ pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Re
             .transpose()?;
 
         download_image_cli(
-            &client,
+            client,
             current_deviation,
             current_deviation_extended,
-            &options,
+            options,
             image_number,
         )
         .await?;
     } else if current_deviation.is_film() {
-        download_film_cli(&client, current_deviation).await?;
+        download_film_cli(client, current_deviation).await?;
     } else {
         bail!("unknown deviation type: {}", current_deviation.kind);
     }
@@ -323,11 +341,11 @@ async fn download_image_cli(
 
         let mut url = None;
         if current_deviation_extended.can_download_additional_media() {
-            url = additional_media.media.base_uri.clone().map(|mut url| {
+            url = additional_media.media.base_uri.as_ref().cloned().map(|mut url| {
                 // Some images require a token, some don't.
                 // I don't know what causes the token to be required.
                 // Regardless, always sending a token when possible doesn't seem to cause issues.
-                match additional_media.media.token.first().as_ref() {
+                match additional_media.media.tokens().first().as_ref() {
                     Some(token) => {
                         url.query_pairs_mut().append_pair("token", token);
                         url
@@ -0,0 +1,85 @@
+use crate::{load_config_cli, try_signin_cli};
+use anyhow::Context;
+use std::path::PathBuf;
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "drift")]
+#[argh(
+    description = "compare the layout of a page against a recorded baseline, to spot layout changes"
+)]
+pub struct Options {
+    #[argh(positional, description = "the page url")]
+    pub url: String,
+
+    #[argh(
+        option,
+        short = 'b',
+        long = "baseline",
+        description = "the path to the baseline json file"
+    )]
+    pub baseline: PathBuf,
+
+    #[argh(
+        switch,
+        long = "record",
+        description = "add the page to the baseline instead of comparing it"
+    )]
+    pub record: bool,
+
+    #[argh(switch, long = "no-login", description = "do not try to log in")]
+    pub no_login: bool,
+}
+
+pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Result<()> {
+    let config = load_config_cli().await;
+
+    if !options.no_login {
+        try_signin_cli(
+            &client,
+            config.username.as_deref(),
+            config.password.as_deref(),
+        )
+        .await?;
+    }
+
+    let mut baseline = match tokio::fs::read(&options.baseline).await {
+        Ok(data) => serde_json::from_slice(&data).with_context(|| {
+            format!(
+                "failed to parse baseline \"{}\"",
+                options.baseline.display()
+            )
+        })?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound && options.record => {
+            deviantart::SchemaBaseline::new()
+        }
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!("failed to read baseline \"{}\"", options.baseline.display())
+            });
+        }
+    };
+
+    let initial_state = client
+        .scrape_initial_state(&options.url)
+        .await
+        .context("failed to scrape webpage")?;
+
+    if options.record {
+        baseline.record_value(&initial_state);
+        let data = serde_json::to_vec_pretty(&baseline).context("failed to serialize baseline")?;
+        tokio::fs::write(&options.baseline, data)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to write baseline \"{}\"",
+                    options.baseline.display()
+                )
+            })?;
+        println!("recorded {} paths", baseline.paths.len());
+    } else {
+        let report = baseline.report_value(&initial_state);
+        print!("{report}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use anyhow::Context;
+use std::path::PathBuf;
+
+#[derive(argh::FromArgs)]
+#[argh(subcommand, name = "import-cookies")]
+#[argh(description = "import a browser session from a Netscape cookies.txt file")]
+pub struct Options {
+    #[argh(positional, description = "the path to the cookies.txt file")]
+    pub path: PathBuf,
+}
+
+pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Result<()> {
+    let file = std::fs::File::open(&options.path)
+        .with_context(|| format!("failed to open \"{}\"", options.path.display()))?;
+    let count = client
+        .load_netscape_cookies(std::io::BufReader::new(file))
+        .await
+        .context("failed to import cookies")?;
+    println!("imported {count} cookies");
+
+    let session = client
+        .session()
+        .await
+        .context("failed to check imported session")?;
+    match session.username.as_deref() {
+        Some(username) if session.is_logged_in => println!("logged in as {username}"),
+        _ => eprintln!("the imported cookies are not logged in"),
+    }
+
+    Ok(())
+}
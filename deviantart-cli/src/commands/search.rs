@@ -1,5 +1,6 @@
 use crate::{load_config_cli, try_signin_cli};
 use anyhow::Context;
+use deviantart::DeviationSource;
 
 #[derive(argh::FromArgs)]
 #[argh(subcommand, name = "search")]
@@ -27,17 +28,26 @@ pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Re
         .await?;
     }
 
-    let mut search_cursor = client.search(&options.query, options.cursor.as_deref());
-    let results = search_cursor
-        .next_page()
+    search(&client, &options).await
+}
+
+/// Search and print a page of results.
+async fn search<S>(source: &S, options: &Options) -> anyhow::Result<()>
+where
+    S: DeviationSource,
+{
+    let search_page = source
+        .get_search_page(&options.query, options.cursor.as_deref())
         .await
-        .with_context(|| format!("failed to search for '{}'", &options.query))
-        .and_then(|_| {
-            search_cursor
-                .current_deviations()
-                .context("missing page")?
-                .context("failed to look up deviations")
-        })?;
+        .with_context(|| format!("failed to search for '{}'", &options.query))?;
+    let results = &search_page.deviations;
+
+    if !search_page.other_items.is_empty() {
+        eprintln!(
+            "Warning: skipped {} results that are not deviations",
+            search_page.other_items.len()
+        );
+    }
 
     if results.is_empty() {
         println!("no results for '{}'", &options.query);
@@ -45,12 +55,20 @@ pub async fn execute(client: deviantart::Client, options: Options) -> anyhow::Re
         println!("Results");
         for (i, deviation) in results.iter().enumerate() {
             println!("{}) {}", i + 1, deviation.title);
+            println!(
+                "Author: {}",
+                deviation.author_username().unwrap_or("(unknown)")
+            );
             println!("Id: {}", deviation.deviation_id);
             println!("Kind: {}", deviation.kind);
             println!("Url: {}", deviation.url);
             println!("Is downloadable: {}", deviation.is_downloadable);
             println!();
         }
+
+        if let Some(next_cursor) = search_page.next_cursor.as_deref() {
+            println!("Next Cursor: {next_cursor}");
+        }
     }
 
     Ok(())
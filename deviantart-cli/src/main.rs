@@ -5,6 +5,7 @@ pub mod util;
 use self::config::Config;
 use anyhow::Context;
 use anyhow::bail;
+use deviantart::types::scraped_webpage_info::FromHtmlStrError;
 use std::path::PathBuf;
 
 #[derive(argh::FromArgs)]
@@ -20,11 +21,49 @@ enum SubCommand {
     Login(self::commands::login::Options),
     Search(self::commands::search::Options),
     Download(self::commands::download::Options),
+    ImportCookies(self::commands::import_cookies::Options),
+    Drift(self::commands::drift::Options),
 }
 
 fn main() -> anyhow::Result<()> {
     let options: Options = argh::from_env();
-    real_main(options)
+    let result = real_main(options);
+    if let Err(error) = result.as_ref()
+        && let Some(hint) = get_error_hint(error)
+    {
+        eprintln!("hint: {hint}");
+    }
+    result
+}
+
+/// Get a hint on how to fix an error, if there is one.
+fn get_error_hint(error: &anyhow::Error) -> Option<&'static str> {
+    let error = error
+        .chain()
+        .find_map(|error| error.downcast_ref::<deviantart::Error>())?;
+
+    match error {
+        deviantart::Error::NotFound { .. } | deviantart::Error::Deleted { .. } => {
+            Some("check the url, the page may have been moved or deleted")
+        }
+        deviantart::Error::LoginRequired { .. }
+        | deviantart::Error::MatureContentGated { .. }
+        | deviantart::Error::InvalidScrapedWebPage(FromHtmlStrError::MatureContentGate {
+            ..
+        }) => Some("log in with the `login` command or pass a username and password"),
+        deviantart::Error::RateLimited { .. } => Some("wait a few minutes before trying again"),
+        deviantart::Error::BlockedByChallenge { .. }
+        | deviantart::Error::InvalidScrapedWebPage(FromHtmlStrError::Challenge { .. }) => Some(
+            "DeviantArt is blocking automated requests, try again later or from a different network",
+        ),
+        deviantart::Error::InvalidScrapedWebPage(FromHtmlStrError::Maintenance { .. }) => {
+            Some("DeviantArt is down for maintenance, try again later")
+        }
+        deviantart::Error::InvalidScrapedWebPage(FromHtmlStrError::NotFound { .. }) => {
+            Some("check the url, the page may have been moved or deleted")
+        }
+        _ => None,
+    }
 }
 
 fn real_main(options: Options) -> anyhow::Result<()> {
@@ -39,21 +78,94 @@ fn real_main(options: Options) -> anyhow::Result<()> {
 }
 
 async fn async_main(options: Options) -> anyhow::Result<()> {
-    let client = deviantart::Client::new();
-
-    match options.subcommand {
-        SubCommand::Login(options) => {
-            self::commands::login::execute(client, options).await?;
+    let client = build_client()?;
+
+    // Commands take the client, so keep a handle to save the session afterwards.
+    let session_client = client.clone();
+    let result = match options.subcommand {
+        SubCommand::Login(options) => self::commands::login::execute(client, options).await,
+        SubCommand::Search(options) => self::commands::search::execute(client, options).await,
+        SubCommand::Download(options) => self::commands::download::execute(client, options).await,
+        SubCommand::ImportCookies(options) => {
+            self::commands::import_cookies::execute(client, options).await
         }
-        SubCommand::Search(options) => {
-            self::commands::search::execute(client, options).await?;
+        SubCommand::Drift(options) => self::commands::drift::execute(client, options).await,
+    };
+
+    // Save cookies the server refreshed even if the command failed.
+    if let Err(error) = session_client.save_session_if_changed().await {
+        eprintln!("failed to save cookie jar: {error:?}");
+    }
+
+    result
+}
+
+/// Build the client, persisting the session in the cookie file if there is one.
+fn build_client() -> anyhow::Result<deviantart::Client> {
+    let cookie_file_path = match get_cookie_file_path() {
+        Ok(cookie_file_path) => Some(cookie_file_path),
+        Err(error) => {
+            eprintln!("failed to get cookie file path: {error:?}");
+            None
         }
-        SubCommand::Download(options) => {
-            self::commands::download::execute(client, options).await?;
+    };
+
+    if let Some(cookie_file_path) = cookie_file_path {
+        match deviantart::Client::builder()
+            .event_handler(print_client_event)
+            .session_store(deviantart::FileSessionStore::new(cookie_file_path))
+            .build()
+        {
+            Ok(client) => return Ok(client),
+            Err(error) => {
+                eprintln!("failed to load cookie jar: {error:?}");
+            }
         }
     }
 
-    Ok(())
+    deviantart::Client::builder()
+        .event_handler(print_client_event)
+        .build()
+        .context("failed to build client")
+}
+
+fn print_client_event(event: &deviantart::ClientEvent<'_>) {
+    match event {
+        deviantart::ClientEvent::RateLimited { delay, .. } => {
+            eprintln!("rate limited, waiting {}s", delay.as_secs_f32().ceil());
+        }
+        deviantart::ClientEvent::RetryAfterCapped {
+            requested, delay, ..
+        } => {
+            eprintln!(
+                "server asked to wait {}s, waiting {}s instead",
+                requested.as_secs(),
+                delay.as_secs()
+            );
+        }
+        deviantart::ClientEvent::Retry {
+            url,
+            status,
+            retry,
+            delay,
+        } => {
+            match status {
+                Some(status) => eprint!("request to \"{url}\" failed with status {status}"),
+                None => eprint!("request to \"{url}\" failed"),
+            }
+            eprintln!(", retry #{retry} in {:.1}s", delay.as_secs_f32());
+        }
+        deviantart::ClientEvent::Reauthenticated { .. } => {
+            eprintln!("session expired, logged in again");
+        }
+        deviantart::ClientEvent::ReauthenticationFailed { error, .. } => {
+            eprintln!("session expired, failed to log in again: {error}");
+        }
+        deviantart::ClientEvent::SessionSaveFailed { error } => {
+            eprintln!("failed to save cookie jar: {error:?}");
+        }
+        _ => {}
+    }
 }
 
 async fn try_signin_cli(
@@ -61,10 +173,6 @@ async fn try_signin_cli(
     username: Option<&str>,
     password: Option<&str>,
 ) -> anyhow::Result<()> {
-    if let Err(e) = load_cookie_jar(client).await {
-        eprintln!("failed to load cookie jar: {e:?}");
-    }
-
     if !client
         .is_logged_in_online()
         .await
@@ -73,19 +181,33 @@ async fn try_signin_cli(
         match (username, password) {
             (Some(username), Some(password)) => {
                 println!("logging in...");
-                client
-                    .login(username, password)
+                let step = client
+                    .start_login(username, password)
                     .await
                     .context("failed to login")?;
+                if let deviantart::LoginStep::VerificationRequired(mut pending) = step {
+                    loop {
+                        let code =
+                            prompt_line(format!("enter the {} verification code: ", pending.kind))
+                                .await?;
+                        match client
+                            .submit_verification_code(&mut pending, code.trim())
+                            .await
+                        {
+                            Ok(()) => break,
+                            Err(deviantart::Error::LoginFailed(
+                                deviantart::LoginFailure::InvalidVerificationCode,
+                            )) => {
+                                println!("invalid verification code");
+                            }
+                            Err(error) => {
+                                return Err(error).context("failed to verify login");
+                            }
+                        }
+                    }
+                }
                 println!("logged in");
                 println!();
-
-                if let Err(e) = save_cookie_jar(client)
-                    .await
-                    .context("failed to save cookies")
-                {
-                    println!("{e:?}");
-                }
             }
             (None, Some(_password)) => {
                 bail!("missing username");
@@ -102,6 +224,21 @@ async fn try_signin_cli(
     Ok(())
 }
 
+/// Print a prompt and read a line from stdin.
+async fn prompt_line(prompt: String) -> anyhow::Result<String> {
+    tokio::task::spawn_blocking(move || {
+        use std::io::Write;
+
+        print!("{prompt}");
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        Ok(line)
+    })
+    .await?
+}
+
 async fn load_config_cli() -> Config {
     Config::load().await.unwrap_or_else(|e| {
         println!("failed to load config: {e:?}");
@@ -113,23 +250,3 @@ fn get_cookie_file_path() -> anyhow::Result<PathBuf> {
     let base_dirs = directories_next::BaseDirs::new().context("failed to get base dirs")?;
     Ok(base_dirs.data_dir().join("deviantart/cookies.json"))
 }
-
-async fn load_cookie_jar(client: &deviantart::Client) -> anyhow::Result<()> {
-    use std::{fs::File, io::BufReader};
-
-    let cookie_file = File::open(get_cookie_file_path()?).context("failed to read cookies")?;
-    client
-        .load_json_cookies(BufReader::new(cookie_file))
-        .await?;
-    Ok(())
-}
-
-async fn save_cookie_jar(client: &deviantart::Client) -> anyhow::Result<()> {
-    use std::fs::File;
-
-    let cookie_file =
-        File::create(get_cookie_file_path()?).context("failed to create cookie file")?;
-    client.save_json_cookies(cookie_file).await?;
-
-    Ok(())
-}
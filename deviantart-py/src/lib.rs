@@ -1,4 +1,9 @@
 use anyhow::Context;
+use deviantart::DeviationSource;
+use deviantart::types::scraped_webpage_info::FromHtmlStrError;
+use pyo3::exceptions::PyConnectionError;
+use pyo3::exceptions::PyLookupError;
+use pyo3::exceptions::PyPermissionError;
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
@@ -25,6 +30,25 @@ fn get_url_file_name(url: &str) -> PyResult<String> {
     Ok(file_name.to_string())
 }
 
+/// Convert a library error into the closest matching python exception.
+fn deviantart_error_to_py_err(error: deviantart::Error) -> PyErr {
+    let message = error.to_string();
+    match error {
+        deviantart::Error::NotFound { .. } | deviantart::Error::Deleted { .. } => {
+            PyLookupError::new_err(message)
+        }
+        deviantart::Error::LoginRequired { .. }
+        | deviantart::Error::MatureContentGated { .. }
+        | deviantart::Error::InvalidScrapedWebPage(FromHtmlStrError::MatureContentGate {
+            ..
+        }) => PyPermissionError::new_err(message),
+        deviantart::Error::RateLimited { .. } | deviantart::Error::BlockedByChallenge { .. } => {
+            PyConnectionError::new_err(message)
+        }
+        _ => PyRuntimeError::new_err(message),
+    }
+}
+
 #[pyclass]
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Deviation {
@@ -156,61 +180,40 @@ impl Folder {
     }
 }
 
-#[pyclass]
-pub struct Client {
-    client: deviantart::Client,
-}
-
-#[pymethods]
-impl Client {
-    #[new]
-    pub fn new() -> Self {
-        Self {
-            client: deviantart::Client::new(),
-        }
-    }
-
-    /// Get metadata for a deviation.
-    pub fn get_deviation(&self, source: Bound<'_, PyAny>) -> PyResult<Deviation> {
-        let tokio_rt = TOKIO_RT
-            .as_ref()
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
-
-        let source = if let Ok(url) = source.cast::<PyString>() {
-            url.to_cow()?.into_owned()
-        } else if let Ok(id) = source.extract::<u64>() {
-            format!("https://www.deviantart.com/view/{id}")
-        } else {
-            return Err(PyValueError::new_err(
-                "source must be a deviation id or a url",
-            ));
-        };
-
-        let scraped_webpage_info = tokio_rt
-            .block_on(async { self.client.scrape_webpage(source.as_str()).await })
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
-
-        let current_deviation = scraped_webpage_info
-            .get_current_deviation()
-            .ok_or_else(|| PyRuntimeError::new_err("failed to get current deviation"))?;
-
-        let current_deviation_extended = scraped_webpage_info
-            .get_current_deviation_extended()
-            .ok_or_else(|| PyRuntimeError::new_err("failed to get current deviation extended"))?;
-
-        let download_url = current_deviation_extended
-            .download
-            .as_ref()
-            .map(|download| download.url.clone())
-            .or_else(|| current_deviation.get_download_url())
-            .map(String::from);
-
-        let fullview_url = current_deviation
-            .get_fullview_url(Default::default())
-            .context("failed to get fullview url")
-            .map(String::from)?;
-
-        let additional_media_download_urls = current_deviation_extended
+/// Get metadata for a deviation from a [`DeviationSource`].
+fn get_deviation<S>(source: &S, url: &str) -> PyResult<Deviation>
+where
+    S: DeviationSource,
+{
+    let tokio_rt = TOKIO_RT
+        .as_ref()
+        .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
+
+    let deviation_info = tokio_rt
+        .block_on(source.get_deviation(url))
+        .map_err(deviantart_error_to_py_err)?;
+
+    let current_deviation = &deviation_info.deviation;
+
+    let current_deviation_extended = deviation_info
+        .deviation_extended
+        .as_ref()
+        .ok_or_else(|| PyRuntimeError::new_err("failed to get current deviation extended"))?;
+
+    let download_url = current_deviation_extended
+        .download
+        .as_ref()
+        .map(|download| download.url.clone())
+        .or_else(|| current_deviation.get_download_url())
+        .map(String::from);
+
+    let fullview_url = current_deviation
+        .get_fullview_url(Default::default())
+        .context("failed to get fullview url")
+        .map(String::from)?;
+
+    let additional_media_download_urls =
+        current_deviation_extended
             .additional_media
             .as_ref()
             .map(|additional_media| {
@@ -224,12 +227,13 @@ impl Client {
                         additional_media
                             .media
                             .base_uri
-                            .clone()
+                            .as_ref()
+                            .cloned()
                             .map(|mut url| {
                                 // Some images require a token, some don't.
                                 // I don't know what causes the token to be required.
                                 // Regardless, always sending a token when possible doesn't seem to cause issues.
-                                match additional_media.media.token.first().as_ref() {
+                                match additional_media.media.tokens().first().as_ref() {
                                     Some(token) => {
                                         url.query_pairs_mut().append_pair("token", token);
                                         url
@@ -241,37 +245,91 @@ impl Client {
                     })
                     .collect()
             });
-        let additional_media_fullview_urls = current_deviation_extended
-            .additional_media
-            .as_ref()
-            .map(|additional_media| {
-                additional_media
-                    .iter()
-                    .map(|additional_media| {
-                        additional_media
-                            .media
-                            .get_fullview_url(deviantart::GetFullviewUrlOptions {
-                                strp: Some(false),
-                                quality: Some(100),
-                                png: true,
-                            })
-                            .map(String::from)
-                    })
-                    .collect::<Result<_, _>>()
-            })
-            .transpose()
-            .context("failed to get fullview urls for additional media")?;
-
-        Ok(Deviation {
-            id: current_deviation.deviation_id,
-            title: current_deviation.title.clone(),
-            description: current_deviation_extended.description.clone(),
-            kind: current_deviation.kind.clone(),
-            download_url,
-            fullview_url,
-            additional_media_download_urls,
-            additional_media_fullview_urls,
+    let additional_media_fullview_urls = current_deviation_extended
+        .additional_media
+        .as_ref()
+        .map(|additional_media| {
+            additional_media
+                .iter()
+                .map(|additional_media| {
+                    additional_media
+                        .media
+                        .get_fullview_url(deviantart::GetFullviewUrlOptions {
+                            strp: Some(false),
+                            quality: Some(100),
+                            png: true,
+                        })
+                        .map(String::from)
+                })
+                .collect::<Result<_, _>>()
         })
+        .transpose()
+        .context("failed to get fullview urls for additional media")?;
+
+    Ok(Deviation {
+        id: current_deviation.deviation_id.0,
+        title: current_deviation.title.clone(),
+        description: current_deviation_extended.description.as_ref().cloned(),
+        kind: current_deviation.kind.to_string(),
+        download_url,
+        fullview_url,
+        additional_media_download_urls,
+        additional_media_fullview_urls,
+    })
+}
+
+/// Get a folder from a [`DeviationSource`].
+fn get_folder<S>(source: &S, url: &str) -> PyResult<Folder>
+where
+    S: DeviationSource,
+{
+    let tokio_rt = TOKIO_RT
+        .as_ref()
+        .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
+
+    let folder_info = tokio_rt
+        .block_on(source.list_folder(url))
+        .map_err(deviantart_error_to_py_err)?;
+
+    Ok(Folder {
+        id: folder_info.folder_id.as_i64(),
+        name: folder_info.name,
+        owner_name: folder_info.owner.username,
+        deviation_ids: folder_info
+            .deviation_ids
+            .into_iter()
+            .map(u64::from)
+            .collect(),
+    })
+}
+
+#[pyclass]
+pub struct Client {
+    client: deviantart::Client,
+}
+
+#[pymethods]
+impl Client {
+    #[new]
+    pub fn new() -> Self {
+        Self {
+            client: deviantart::Client::new(),
+        }
+    }
+
+    /// Get metadata for a deviation.
+    pub fn get_deviation(&self, source: Bound<'_, PyAny>) -> PyResult<Deviation> {
+        let source = if let Ok(url) = source.cast::<PyString>() {
+            url.to_cow()?.into_owned()
+        } else if let Ok(id) = source.extract::<u64>() {
+            format!("https://www.deviantart.com/view/{id}")
+        } else {
+            return Err(PyValueError::new_err(
+                "source must be a deviation id or a url",
+            ));
+        };
+
+        get_deviation(&self.client, &source)
     }
 
     /// Download a deviation.
@@ -319,7 +377,7 @@ impl Client {
 
         tokio_rt
             .block_on(self.client.is_logged_in_online())
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))
+            .map_err(deviantart_error_to_py_err)
     }
 
     /// Load cookies.
@@ -358,74 +416,12 @@ impl Client {
 
         tokio_rt
             .block_on(self.client.login(username, password))
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))
+            .map_err(deviantart_error_to_py_err)
     }
 
     /// Get the folder given by the url.
     pub fn get_folder(&self, url: &str) -> PyResult<Folder> {
-        let tokio_rt = TOKIO_RT
-            .as_ref()
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
-
-        let scraped_webpage_info = tokio_rt
-            .block_on(async { self.client.scrape_webpage(url).await })
-            .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
-
-        let id = scraped_webpage_info
-            .get_current_folder_id()
-            .ok_or_else(|| PyRuntimeError::new_err("missing folder id"))?;
-
-        let stream = scraped_webpage_info
-            .get_folder_deviations_stream(id)
-            .ok_or_else(|| PyRuntimeError::new_err("missing folder deviation stream"))?;
-
-        let mut deviation_ids = stream.items.clone();
-
-        let folder_entity = scraped_webpage_info
-            .get_gallery_folder_entity(id)
-            .ok_or_else(|| PyRuntimeError::new_err("missing gallery folder entity"))?;
-
-        let user_entity = scraped_webpage_info
-            .get_user_entity(folder_entity.owner)
-            .ok_or_else(|| PyRuntimeError::new_err("missing user entity"))?;
-
-        let owner_name = user_entity.username.clone();
-
-        if stream.has_more {
-            tokio_rt
-                .block_on(async {
-                    let mut has_more = true;
-                    while has_more {
-                        let offset = u64::try_from(deviation_ids.len()).unwrap();
-                        let response = self
-                            .client
-                            .list_folder_contents(
-                                &owner_name,
-                                id,
-                                offset,
-                                &scraped_webpage_info.config.csrf_token,
-                            )
-                            .await?;
-                        deviation_ids.extend(
-                            response
-                                .results
-                                .iter()
-                                .map(|deviation| deviation.deviation_id),
-                        );
-                        has_more = response.has_more;
-                    }
-
-                    Result::<_, deviantart::Error>::Ok(())
-                })
-                .map_err(|error| PyRuntimeError::new_err(error.to_string()))?;
-        }
-
-        Ok(Folder {
-            id,
-            name: folder_entity.name.clone(),
-            owner_name,
-            deviation_ids,
-        })
+        get_folder(&self.client, url)
     }
 }
 
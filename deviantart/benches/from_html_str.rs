@@ -0,0 +1,186 @@
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use deviantart::LazyScrapedWebPageInfo;
+use deviantart::ScrapedStashInfo;
+use deviantart::ScrapedWebPageInfo;
+use regex::Regex;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::hint::black_box;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+const SCRAPED_WEBPAGE: &str = include_str!("../test_data/scraped_webpage.json");
+
+/// An allocator that tracks the peak number of allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        let allocated = ALLOCATED.fetch_add(new_size, Ordering::Relaxed) + new_size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Get the peak number of bytes allocated while running a function.
+fn measure_peak_memory<T>(func: impl FnOnce() -> T) -> usize {
+    let base = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(base, Ordering::Relaxed);
+    let value = func();
+    let peak = PEAK.load(Ordering::Relaxed) - base;
+    drop(value);
+    peak
+}
+
+/// Make a html page with the test webpage info as its initial state.
+fn make_webpage_html() -> String {
+    let json: serde_json::Value =
+        serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse test data");
+    let literal = json
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\'', "\\'");
+
+    format!(
+        "<html><head><script>window.__INITIAL_STATE__ = JSON.parse(\"{literal}\");</script></head></html>"
+    )
+}
+
+/// Make a html page with stash page data.
+fn make_stash_html() -> String {
+    let json = serde_json::json!({
+        "csrf": "token",
+        "deviationid": 1,
+        "film": null,
+        "deviation_width": 1920,
+        "deviation_height": 1080,
+        "padding": "x".repeat(100_000),
+    });
+
+    format!("<html><script>deviantART.pageData={json};</script></html>")
+}
+
+/// The regex-based extraction that [`ScrapedWebPageInfo::from_html_str`] used to use.
+fn webpage_from_html_str_regex(input: &str) -> ScrapedWebPageInfo {
+    static REGEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"window\.__INITIAL_STATE__ = JSON\.parse\("(.*)"\);"#).unwrap()
+    });
+
+    let capture = REGEX
+        .captures(input)
+        .and_then(|captures| captures.get(1))
+        .expect("missing initial state");
+    let capture = capture
+        .as_str()
+        .replace("\\\"", "\"")
+        .replace("\\'", "'")
+        .replace("\\\\", "\\");
+    serde_json::from_str(&capture).expect("failed to parse")
+}
+
+/// The regex-based extraction that [`ScrapedStashInfo::from_html_str`] used to use.
+fn stash_from_html_str_regex(input: &str) -> ScrapedStashInfo {
+    static REGEX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"deviantART.pageData=(.*);"#).unwrap());
+
+    let capture = REGEX
+        .captures(input)
+        .and_then(|captures| captures.get(1))
+        .expect("missing page data");
+    serde_json::from_str(capture.as_str()).expect("failed to parse")
+}
+
+fn webpage(c: &mut Criterion) {
+    let html = make_webpage_html();
+
+    // Warm up the regex and the reused buffer, so that only per-call allocations are counted.
+    webpage_from_html_str_regex(&html);
+    ScrapedWebPageInfo::from_html_str(&html).expect("failed to parse");
+    println!(
+        "webpage peak memory: regex {} bytes, scanner {} bytes",
+        measure_peak_memory(|| webpage_from_html_str_regex(&html)),
+        measure_peak_memory(|| ScrapedWebPageInfo::from_html_str(&html)),
+    );
+
+    let mut group = c.benchmark_group("ScrapedWebPageInfo::from_html_str");
+    group.bench_function("regex", |b| {
+        b.iter(|| webpage_from_html_str_regex(black_box(&html)))
+    });
+    group.bench_function("scanner", |b| {
+        b.iter(|| ScrapedWebPageInfo::from_html_str(black_box(&html)))
+    });
+    group.finish();
+}
+
+fn lazy_webpage(c: &mut Criterion) {
+    let html = make_webpage_html();
+
+    let mut group = c.benchmark_group("LazyScrapedWebPageInfo::from_html_str");
+    group.bench_function("eager", |b| {
+        b.iter(|| {
+            let scraped_webpage =
+                ScrapedWebPageInfo::from_html_str(black_box(&html)).expect("failed to parse");
+            scraped_webpage
+                .get_current_deviation()
+                .map(|deviation| deviation.deviation_id)
+        })
+    });
+    group.bench_function("lazy", |b| {
+        b.iter(|| {
+            let scraped_webpage =
+                LazyScrapedWebPageInfo::from_html_str(black_box(&html)).expect("failed to parse");
+            let id = scraped_webpage.get_current_deviation_id()?;
+            scraped_webpage
+                .get_deviation_ref_by_id(id)
+                .expect("failed to parse deviation")
+                .map(|deviation| deviation.deviation_id)
+        })
+    });
+    group.finish();
+}
+
+fn stash(c: &mut Criterion) {
+    let html = make_stash_html();
+
+    stash_from_html_str_regex(&html);
+    println!(
+        "stash peak memory: regex {} bytes, scanner {} bytes",
+        measure_peak_memory(|| stash_from_html_str_regex(&html)),
+        measure_peak_memory(|| ScrapedStashInfo::from_html_str(&html)),
+    );
+
+    let mut group = c.benchmark_group("ScrapedStashInfo::from_html_str");
+    group.bench_function("regex", |b| {
+        b.iter(|| stash_from_html_str_regex(black_box(&html)))
+    });
+    group.bench_function("scanner", |b| {
+        b.iter(|| ScrapedStashInfo::from_html_str(black_box(&html)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, webpage, lazy_webpage, stash);
+criterion_main!(benches);
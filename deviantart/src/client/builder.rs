@@ -0,0 +1,367 @@
+use super::ACCEPT_LANGUAGE_VALUE;
+use super::ACCEPT_VALUE;
+use super::USER_AGENT_STR;
+use super::session_store::TrackingCookieStore;
+use crate::Client;
+use crate::ClientEvent;
+use crate::CredentialProvider;
+use crate::Endpoints;
+use crate::Error;
+use crate::EventHandler;
+use crate::Fixtures;
+use crate::RateLimiter;
+use crate::RetryPolicy;
+use crate::SessionStore;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use reqwest_cookie_store::CookieStoreMutex;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// A TLS backend for the http client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TlsBackend {
+    /// Use rustls
+    #[cfg(feature = "rustls-tls")]
+    Rustls,
+
+    /// Use the platform's native TLS implementation
+    #[cfg(feature = "native-tls")]
+    NativeTls,
+}
+
+impl TlsBackend {
+    /// Select this backend on a http client builder.
+    #[cfg_attr(
+        not(any(feature = "rustls-tls", feature = "native-tls")),
+        allow(unused_variables)
+    )]
+    fn apply(self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        match self {
+            #[cfg(feature = "rustls-tls")]
+            Self::Rustls => builder.tls_backend_rustls(),
+            #[cfg(feature = "native-tls")]
+            Self::NativeTls => builder.tls_backend_native(),
+        }
+    }
+}
+
+/// A builder for a [`Client`].
+#[derive(Debug)]
+pub struct ClientBuilder {
+    user_agent: Option<String>,
+    accept_language: Option<String>,
+    default_headers: HeaderMap,
+    proxies: Vec<reqwest::Proxy>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    tls_backend: Option<TlsBackend>,
+    reqwest_client: Option<(reqwest::Client, Arc<CookieStoreMutex>)>,
+    cookie_store: Option<Arc<CookieStoreMutex>>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    event_handler: Option<EventHandler>,
+    endpoints: Endpoints,
+    fixtures: Option<Fixtures>,
+    credential_provider: Option<CredentialProvider>,
+    session_store: Option<Arc<dyn SessionStore>>,
+}
+
+impl ClientBuilder {
+    /// Make a new [`ClientBuilder`] with the default settings.
+    pub fn new() -> Self {
+        Self {
+            user_agent: None,
+            accept_language: None,
+            default_headers: HeaderMap::new(),
+            proxies: Vec::new(),
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            tls_backend: None,
+            reqwest_client: None,
+            cookie_store: None,
+            retry_policy: RetryPolicy::new(),
+            rate_limiter: None,
+            event_handler: None,
+            endpoints: Endpoints::new(),
+            fixtures: None,
+            credential_provider: None,
+            session_store: None,
+        }
+    }
+
+    /// Set the user agent.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the locale, with the value of the `Accept-Language` header.
+    ///
+    /// For example, `"de-DE,de;q=0.9"`.
+    pub fn accept_language(mut self, accept_language: impl Into<String>) -> Self {
+        self.accept_language = Some(accept_language.into());
+        self
+    }
+
+    /// Add a header to send with every request.
+    ///
+    /// This overrides any header with the same name that this library sends by default.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Add a proxy.
+    ///
+    /// SOCKS proxies require the `socks` feature.
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Set the timeout for connecting to a server.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for each read from a server.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for an entire request, from connecting until the response body is read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the TLS backend.
+    pub fn tls_backend(mut self, tls_backend: TlsBackend) -> Self {
+        self.tls_backend = Some(tls_backend);
+        self
+    }
+
+    /// Use a pre-built http client.
+    ///
+    /// The user agent, headers, proxies, timeouts, and TLS backend of this builder are ignored if this is set.
+    /// `cookie_store` must be the cookie provider of the given client.
+    /// The client uses it for logging in and out, for [`Client::session`], and for importing cookies,
+    /// so these do nothing if the given client reads its cookies from somewhere else.
+    ///
+    /// This cannot be combined with [`ClientBuilder::cookie_store`] or [`ClientBuilder::session_store`].
+    pub fn reqwest_client(
+        mut self,
+        client: reqwest::Client,
+        cookie_store: Arc<CookieStoreMutex>,
+    ) -> Self {
+        self.reqwest_client = Some((client, cookie_store));
+        self
+    }
+
+    /// Use an existing cookie store.
+    ///
+    /// This can be used to start with a pre-populated cookie store,
+    /// or to share a cookie store between clients.
+    pub fn cookie_store(mut self, cookie_store: Arc<CookieStoreMutex>) -> Self {
+        self.cookie_store = Some(cookie_store);
+        self
+    }
+
+    /// Set the policy for retrying failed requests.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use an existing rate limiter.
+    ///
+    /// This can be used to share a rate limiter between clients.
+    pub fn rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Set a callback for events, like retries and rate limits.
+    pub fn event_handler<F>(mut self, func: F) -> Self
+    where
+        F: Fn(&ClientEvent<'_>) + Send + Sync + 'static,
+    {
+        self.event_handler = Some(EventHandler::new(func));
+        self
+    }
+
+    /// Set the base urls of the servers the client talks to.
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Record responses to or replay responses from a fixture directory.
+    pub fn fixtures(mut self, fixtures: Fixtures) -> Self {
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    /// Log in again with the given credentials when the session expires.
+    pub fn credential_provider(mut self, credential_provider: CredentialProvider) -> Self {
+        self.credential_provider = Some(credential_provider);
+        self
+    }
+
+    /// Persist the cookie store in a session store.
+    ///
+    /// The saved session is loaded when the client is built,
+    /// and saved again after logging in and logging out.
+    /// Cookies that the server refreshes are saved shortly after they change;
+    /// use [`Client::save_session_if_changed`] before exiting to not lose the last changes.
+    pub fn session_store<S>(mut self, session_store: S) -> Self
+    where
+        S: SessionStore + 'static,
+    {
+        self.session_store = Some(Arc::new(session_store));
+        self
+    }
+
+    /// Build the [`Client`].
+    ///
+    /// This fails if a pre-built http client is combined with a cookie store or a session store.
+    ///
+    /// The session store is loaded synchronously on the calling thread.
+    /// This can block for a while, especially when an `EncryptedSessionStore` derives its key,
+    /// so build the client in [`tokio::task::spawn_blocking`] when calling this from async code.
+    pub fn build(self) -> Result<Client, Error> {
+        if self.reqwest_client.is_some() && self.cookie_store.is_some() {
+            return Err(Error::InvalidBuilder(
+                "a cookie store cannot be used with a pre-built reqwest client",
+            ));
+        }
+        if self.reqwest_client.is_some() && self.session_store.is_some() {
+            return Err(Error::InvalidBuilder(
+                "a session store cannot be used with a pre-built reqwest client",
+            ));
+        }
+
+        let cookies_changed = Arc::new(AtomicBool::new(false));
+        let (client, cookie_store) = match self.reqwest_client {
+            Some((client, cookie_store)) => (client, cookie_store),
+            None => {
+                let cookie_store = self
+                    .cookie_store
+                    .unwrap_or_else(|| Arc::new(CookieStoreMutex::new(Default::default())));
+
+                let accept_language = match self.accept_language {
+                    Some(accept_language) => HeaderValue::try_from(accept_language)?,
+                    None => ACCEPT_LANGUAGE_VALUE.clone(),
+                };
+
+                let mut default_headers = HeaderMap::new();
+                default_headers.insert(reqwest::header::ACCEPT_LANGUAGE, accept_language);
+                default_headers.insert(reqwest::header::ACCEPT, ACCEPT_VALUE.clone());
+                default_headers.insert(
+                    reqwest::header::REFERER,
+                    HeaderValue::from_str(self.endpoints.www.as_str())?,
+                );
+                default_headers.extend(self.default_headers);
+
+                let mut builder = reqwest::Client::builder()
+                    .cookie_provider(Arc::new(TrackingCookieStore {
+                        cookie_store: cookie_store.clone(),
+                        changed: cookies_changed.clone(),
+                    }))
+                    .user_agent(self.user_agent.as_deref().unwrap_or(USER_AGENT_STR))
+                    .default_headers(default_headers);
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.read_timeout {
+                    builder = builder.read_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(tls_backend) = self.tls_backend {
+                    builder = tls_backend.apply(builder);
+                }
+
+                (builder.build()?, cookie_store)
+            }
+        };
+
+        let client = Client {
+            client,
+            cookie_store,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter.unwrap_or_default(),
+            event_handler: self.event_handler,
+            endpoints: Arc::new(self.endpoints),
+            fixtures: self.fixtures,
+            credential_provider: self.credential_provider,
+            session_store: self.session_store,
+            cookies_changed,
+            session_save: Default::default(),
+        };
+        client.load_session()?;
+
+        Ok(client)
+    }
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Build a reqwest client with a cookie store.
+    fn make_reqwest_client() -> (reqwest::Client, Arc<CookieStoreMutex>) {
+        let cookie_store = Arc::new(CookieStoreMutex::new(Default::default()));
+        let client = reqwest::Client::builder()
+            .cookie_provider(cookie_store.clone())
+            .build()
+            .expect("failed to build reqwest client");
+        (client, cookie_store)
+    }
+
+    #[test]
+    fn reqwest_client_conflicts() {
+        let (reqwest_client, reqwest_cookie_store) = make_reqwest_client();
+        let cookie_store = Arc::new(CookieStoreMutex::new(Default::default()));
+        let error = ClientBuilder::new()
+            .reqwest_client(reqwest_client, reqwest_cookie_store)
+            .cookie_store(cookie_store)
+            .build()
+            .expect_err("built a client with a cookie store it cannot use");
+        assert!(matches!(error, Error::InvalidBuilder(_)), "{error:?}");
+
+        let (reqwest_client, reqwest_cookie_store) = make_reqwest_client();
+        let error = ClientBuilder::new()
+            .reqwest_client(reqwest_client, reqwest_cookie_store)
+            .session_store(crate::MemorySessionStore::new())
+            .build()
+            .expect_err("built a client with a session store it cannot track");
+        assert!(matches!(error, Error::InvalidBuilder(_)), "{error:?}");
+
+        // The client uses the cookie store of the reqwest client.
+        let (reqwest_client, reqwest_cookie_store) = make_reqwest_client();
+        let client = ClientBuilder::new()
+            .reqwest_client(reqwest_client, reqwest_cookie_store.clone())
+            .build()
+            .expect("failed to build client");
+        assert!(Arc::ptr_eq(&client.cookie_store, &reqwest_cookie_store));
+    }
+}
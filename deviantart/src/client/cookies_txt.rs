@@ -0,0 +1,247 @@
+use crate::Client;
+use crate::Error;
+use crate::WrapBoxError;
+use cookie_store::Cookie;
+use cookie_store::CookieDomain;
+use cookie_store::CookieExpiration;
+use cookie_store::CookieStore;
+use cookie_store::RawCookie;
+use time::OffsetDateTime;
+use url::Url;
+
+/// The domain that cookies are imported and exported for.
+const COOKIE_DOMAIN: &str = "deviantart.com";
+
+/// The prefix that marks an http-only cookie.
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// Returns `true` if cookies for the given domain should be imported and exported.
+fn is_deviantart_domain(domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    domain.eq_ignore_ascii_case(COOKIE_DOMAIN)
+        || domain
+            .to_ascii_lowercase()
+            .ends_with(&format!(".{COOKIE_DOMAIN}"))
+}
+
+/// Parse the DeviantArt cookies from a Netscape cookies.txt file.
+///
+/// Lines for other domains are skipped without being validated.
+/// Expired cookies are skipped.
+fn parse(text: &str) -> Result<Vec<(Cookie<'static>, Url)>, Error> {
+    let now = OffsetDateTime::now_utc();
+    let mut cookies = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid_line = || Error::InvalidCookiesTxt { line: i + 1 };
+        let fields: Vec<_> = line.split('\t').collect();
+        if !fields
+            .first()
+            .is_some_and(|domain| is_deviantart_domain(domain))
+        {
+            continue;
+        }
+        let [
+            domain,
+            include_subdomains,
+            path,
+            secure,
+            expires,
+            name,
+            value,
+        ] = fields[..]
+        else {
+            return Err(invalid_line());
+        };
+
+        let include_subdomains = include_subdomains.eq_ignore_ascii_case("TRUE");
+        let secure = secure.eq_ignore_ascii_case("TRUE");
+        let expires: i64 = expires.parse().map_err(|_error| invalid_line())?;
+
+        let host = domain.trim_start_matches('.');
+        let mut cookie = RawCookie::build((name, value))
+            .path(path)
+            .secure(secure)
+            .http_only(http_only);
+        if include_subdomains {
+            cookie = cookie.domain(host);
+        }
+        // An expiry of 0 means a session cookie.
+        if expires != 0 {
+            let expires =
+                OffsetDateTime::from_unix_timestamp(expires).map_err(|_error| invalid_line())?;
+            if expires <= now {
+                continue;
+            }
+            cookie = cookie.expires(expires);
+        }
+
+        let url = Url::parse(&format!("https://{host}{path}")).map_err(|_error| invalid_line())?;
+        let cookie =
+            Cookie::try_from_raw_cookie(&cookie.build(), &url).map_err(|_error| invalid_line())?;
+        if !cookie.domain.matches(&url) {
+            return Err(invalid_line());
+        }
+        cookies.push((cookie.into_owned(), url));
+    }
+
+    Ok(cookies)
+}
+
+/// Import the DeviantArt cookies from a Netscape cookies.txt file into a cookie store.
+///
+/// The whole file is parsed before the store is changed,
+/// so an invalid file imports nothing.
+/// This returns the number of imported cookies.
+fn import(cookie_store: &mut CookieStore, text: &str) -> Result<usize, Error> {
+    let cookies = parse(text)?;
+    let count = cookies.len();
+    for (cookie, url) in cookies {
+        cookie_store
+            .insert(cookie, &url)
+            .map_err(|e| Error::CookieStore(WrapBoxError(e.into())))?;
+    }
+
+    Ok(count)
+}
+
+/// Export the DeviantArt cookies in a cookie store as a Netscape cookies.txt file.
+fn export(cookie_store: &CookieStore) -> String {
+    let mut text = String::from("# Netscape HTTP Cookie File\n");
+    for cookie in cookie_store.iter_unexpired() {
+        let (domain, include_subdomains) = match &cookie.domain {
+            CookieDomain::HostOnly(domain) => (domain.clone(), false),
+            CookieDomain::Suffix(domain) => (format!(".{domain}"), true),
+            CookieDomain::NotPresent | CookieDomain::Empty => continue,
+        };
+        if !is_deviantart_domain(&domain) {
+            continue;
+        }
+
+        let expires = match cookie.expires {
+            CookieExpiration::AtUtc(expires) => expires.unix_timestamp(),
+            CookieExpiration::SessionEnd => 0,
+        };
+        let bool_str = |value: bool| if value { "TRUE" } else { "FALSE" };
+
+        if cookie.http_only().unwrap_or(false) {
+            text.push_str(HTTP_ONLY_PREFIX);
+        }
+        text.push_str(&format!(
+            "{domain}\t{}\t{}\t{}\t{expires}\t{}\t{}\n",
+            bool_str(include_subdomains),
+            cookie.path.as_ref(),
+            bool_str(cookie.secure().unwrap_or(false)),
+            cookie.name(),
+            cookie.value(),
+        ));
+    }
+
+    text
+}
+
+impl Client {
+    /// Import DeviantArt cookies from a Netscape cookies.txt reader, like the ones browser extensions export.
+    ///
+    /// Cookies for other domains are ignored.
+    /// This returns the number of imported cookies.
+    /// Use [`Client::session`] to check whether the imported session is logged in.
+    pub async fn load_netscape_cookies<R>(&self, mut reader: R) -> Result<usize, Error>
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        let cookie_store = self.cookie_store.clone();
+        let count = tokio::task::spawn_blocking(move || {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+
+            let mut cookie_store = cookie_store.lock().expect("cookie store is poisoned");
+            import(&mut cookie_store, &text)
+        })
+        .await??;
+        self.save_session_or_emit().await;
+
+        Ok(count)
+    }
+
+    /// Export DeviantArt cookies to a Netscape cookies.txt writer.
+    pub async fn save_netscape_cookies<W>(&self, mut writer: W) -> Result<W, Error>
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        let cookie_store = self.cookie_store.clone();
+        tokio::task::spawn_blocking(move || {
+            let text = {
+                let cookie_store = cookie_store.lock().expect("cookie store is poisoned");
+                export(&cookie_store)
+            };
+            writer.write_all(text.as_bytes())?;
+            Ok(writer)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const COOKIES_TXT: &str = "# Netscape HTTP Cookie File
+# This is a generated file! Do not edit.
+
+#HttpOnly_.deviantart.com\tTRUE\t/\tTRUE\t4102444800\tauth\tsecret
+www.deviantart.com\tFALSE\t/\tFALSE\t0\ttd\t3:1234
+.deviantart.com\tTRUE\t/\tTRUE\t946684800\told\texpired
+.example.com\tTRUE\t/\tFALSE\t4102444800\tother\tvalue
+";
+
+    #[test]
+    fn import_export() {
+        let mut cookie_store = CookieStore::default();
+        let count = import(&mut cookie_store, COOKIES_TXT).expect("failed to import");
+        assert!(count == 2, "{count} != 2");
+
+        let text = export(&cookie_store);
+        assert!(
+            text.contains("#HttpOnly_.deviantart.com\tTRUE\t/\tTRUE\t4102444800\tauth\tsecret\n")
+        );
+        assert!(text.contains("www.deviantart.com\tFALSE\t/\tFALSE\t0\ttd\t3:1234\n"));
+        assert!(!text.contains("expired"));
+        assert!(!text.contains("example.com"));
+
+        let error = import(&mut cookie_store, "deviantart.com\tTRUE\n")
+            .expect_err("imported an invalid line");
+        assert!(
+            matches!(error, Error::InvalidCookiesTxt { line: 1 }),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn import_skips_other_domains() {
+        let mut cookie_store = CookieStore::default();
+        let text = ".example.com\tTRUE\n.deviantart.com\tTRUE\t/\tTRUE\t0\tauth\tsecret\n";
+        let count = import(&mut cookie_store, text).expect("failed to import");
+        assert!(count == 1, "{count} != 1");
+    }
+
+    #[test]
+    fn import_is_all_or_nothing() {
+        let mut cookie_store = CookieStore::default();
+        let text = ".deviantart.com\tTRUE\t/\tTRUE\t0\tauth\tsecret\n.deviantart.com\tTRUE\t/\tTRUE\tsoon\ttd\t1\n";
+        let error = import(&mut cookie_store, text).expect_err("imported an invalid line");
+        assert!(
+            matches!(error, Error::InvalidCookiesTxt { line: 2 }),
+            "{error:?}"
+        );
+        assert!(cookie_store.iter_any().next().is_none());
+    }
+}
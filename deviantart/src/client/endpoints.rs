@@ -0,0 +1,61 @@
+use crate::Error;
+use url::Url;
+
+const WWW_URL: &str = "https://www.deviantart.com/";
+const BACKEND_URL: &str = "https://backend.deviantart.com/";
+
+/// The base urls of the servers that a [`Client`](crate::Client) talks to.
+///
+/// Change these to point a client at a local mock server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// The main site.
+    ///
+    /// This hosts pages, login, search, and the `_puppy` api.
+    pub www: Url,
+
+    /// The backend server.
+    ///
+    /// This hosts the oembed api.
+    pub backend: Url,
+}
+
+impl Endpoints {
+    /// Make a new [`Endpoints`] with the DeviantArt servers.
+    pub fn new() -> Self {
+        Self {
+            www: Url::parse(WWW_URL).expect("invalid www url"),
+            backend: Url::parse(BACKEND_URL).expect("invalid backend url"),
+        }
+    }
+
+    /// Make a new [`Endpoints`] that uses a single base url for every server.
+    pub fn with_base_url(mut base_url: Url) -> Self {
+        // Make sure that joined paths are appended to the base path instead of replacing the last segment.
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+
+        Self {
+            www: base_url.clone(),
+            backend: base_url,
+        }
+    }
+
+    /// Get a url on the main site.
+    pub(crate) fn www_url(&self, path: &str) -> Result<Url, Error> {
+        Ok(self.www.join(path)?)
+    }
+
+    /// Get a url on the backend server.
+    pub(crate) fn backend_url(&self, path: &str) -> Result<Url, Error> {
+        Ok(self.backend.join(path)?)
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,252 @@
+use crate::Error;
+use reqwest::header::HeaderMap;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use std::borrow::Cow;
+use std::path::PathBuf;
+use url::Url;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Form fields that are left out of fixture keys, so recorded logins replay with any credentials.
+const SECRET_FORM_FIELDS: &[&str] = &["username", "password", "code"];
+
+/// Whether a [`Fixtures`] directory is being recorded or replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Send requests over the network, saving every response.
+    Record,
+
+    /// Serve saved responses, without using the network.
+    Replay,
+}
+
+/// A directory of recorded http responses.
+///
+/// This is useful for running tests offline and for making reproducible bug reports.
+/// Responses are keyed by the method, url, and body of their request.
+/// Request bodies may contain passwords, so they are only hashed and never saved.
+/// Login credentials are left out of the hash,
+/// so a recorded login can be replayed without the real username and password.
+/// Response headers are saved so that replayed responses set cookies,
+/// but cookie values are redacted so that recorded sessions cannot be reused.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    /// The fixture directory
+    dir: PathBuf,
+
+    /// The mode
+    mode: FixtureMode,
+}
+
+impl Fixtures {
+    /// Make a new [`Fixtures`] for the given directory.
+    ///
+    /// In record mode, the directory is created if it does not exist.
+    pub fn new(dir: impl Into<PathBuf>, mode: FixtureMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// Get the fixture directory.
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// Get the mode.
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    /// Get the path of the fixture for a request.
+    fn get_path(&self, request: &reqwest::Request) -> PathBuf {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or(&[]);
+        let is_form = request
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .is_some_and(|content_type| content_type == "application/x-www-form-urlencoded");
+        let body = if is_form {
+            Cow::Owned(redact_form(body))
+        } else {
+            Cow::Borrowed(body)
+        };
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for part in [
+            request.method().as_str().as_bytes(),
+            b"\n",
+            request.url().as_str().as_bytes(),
+            b"\n",
+            &body,
+        ] {
+            for byte in part {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        self.dir.join(format!("{hash:016x}.json"))
+    }
+
+    /// Load the recorded response for a request.
+    pub(crate) async fn load(&self, request: &reqwest::Request) -> Result<Fixture, Error> {
+        let path = self.get_path(request);
+        let data = tokio::task::spawn_blocking(move || std::fs::read(path)).await?;
+        let data = match data {
+            Ok(data) => data,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::MissingFixture {
+                    method: request.method().to_string(),
+                    url: request.url().clone(),
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Save the response for a request.
+    pub(crate) async fn save(
+        &self,
+        request: &reqwest::Request,
+        status: reqwest::StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Result<(), Error> {
+        let dir = self.dir.clone();
+        let path = self.get_path(request);
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                let value = if name == reqwest::header::SET_COOKIE {
+                    redact_cookie(value)
+                } else {
+                    value.to_string()
+                };
+                Some((name.to_string(), value))
+            })
+            .collect();
+        let fixture = Fixture {
+            method: request.method().to_string(),
+            url: request.url().clone(),
+            status: status.as_u16(),
+            headers,
+            body: body.into(),
+        };
+        let data = serde_json::to_vec_pretty(&fixture)?;
+
+        tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(path, data)
+        })
+        .await??;
+
+        Ok(())
+    }
+}
+
+/// A recorded response.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Fixture {
+    /// The request method
+    pub method: String,
+
+    /// The request url
+    pub url: Url,
+
+    /// The response status
+    pub status: u16,
+
+    /// The response headers
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+
+    /// The response body
+    pub body: String,
+}
+
+impl Fixture {
+    /// Get the response headers.
+    ///
+    /// Invalid headers are skipped.
+    pub fn header_map(&self) -> HeaderMap {
+        self.headers
+            .iter()
+            .filter_map(|(name, value)| {
+                let name = HeaderName::try_from(name.as_str()).ok()?;
+                let value = HeaderValue::try_from(value.as_str()).ok()?;
+                Some((name, value))
+            })
+            .collect()
+    }
+}
+
+/// Clear the values of secret fields in a urlencoded form.
+fn redact_form(body: &[u8]) -> Vec<u8> {
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    for (name, value) in url::form_urlencoded::parse(body) {
+        if SECRET_FORM_FIELDS.contains(&name.as_ref()) {
+            serializer.append_pair(&name, "");
+        } else {
+            serializer.append_pair(&name, &value);
+        }
+    }
+    serializer.finish().into_bytes()
+}
+
+/// Replace the value of a `Set-Cookie` header, keeping the name and attributes.
+fn redact_cookie(set_cookie: &str) -> String {
+    let (pair, attributes) = match set_cookie.split_once(';') {
+        Some((pair, attributes)) => (pair, Some(attributes)),
+        None => (set_cookie, None),
+    };
+    let name = pair.split_once('=').map_or(pair, |(name, _value)| name);
+
+    match attributes {
+        Some(attributes) => format!("{name}=redacted;{attributes}"),
+        None => format!("{name}=redacted"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redact_cookies() {
+        assert!(
+            redact_cookie("auth=secret; Path=/; Max-Age=3600")
+                == "auth=redacted; Path=/; Max-Age=3600"
+        );
+        assert!(redact_cookie("td=1") == "td=redacted");
+    }
+
+    #[test]
+    fn credentials_are_not_hashed() {
+        let fixtures = Fixtures::new("fixtures", FixtureMode::Replay);
+        let client = reqwest::Client::new();
+        let get_path = |username: &str, password: &str, token: &str| {
+            let request = client
+                .post("https://www.deviantart.com/_sisu/do/signin")
+                .form(&[
+                    ("lu_token", token),
+                    ("username", username),
+                    ("password", password),
+                ])
+                .build()
+                .expect("failed to build request");
+            fixtures.get_path(&request)
+        };
+
+        assert!(get_path("alice", "hunter2", "lu1") == get_path("bob", "letmein", "lu1"));
+        assert!(get_path("alice", "hunter2", "lu1") != get_path("alice", "hunter2", "lu2"));
+    }
+}
@@ -0,0 +1,441 @@
+use crate::Client;
+use crate::Error;
+use crate::Nullable;
+use crate::ScrapedWebPageInfo;
+use url::Url;
+
+/// The `authMode` of login pages that are not waiting for a special step.
+const UNKNOWN_AUTH_MODE: &str = "unknown";
+
+/// The `challenge` form value of pages that are not waiting for a verification code.
+const NO_CHALLENGE: &str = "0";
+
+/// The `challenge` form value of pages that are waiting for a verification code.
+const VERIFICATION_CHALLENGE: &str = "1";
+
+/// A reason that logging in failed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum LoginFailure {
+    /// There is no account with the given username
+    #[error("unknown username")]
+    UnknownUsername,
+
+    /// The password is wrong
+    #[error("wrong password")]
+    WrongPassword,
+
+    /// The account is locked
+    #[error("account locked")]
+    AccountLocked,
+
+    /// A captcha must be solved, which this library cannot do
+    #[error("captcha required")]
+    CaptchaRequired,
+
+    /// DeviantArt asked for a verification code.
+    ///
+    /// Use [`Client::start_login`] to submit the code.
+    #[error("{0} verification required")]
+    VerificationRequired(VerificationKind),
+
+    /// DeviantArt asked for a login step that this library does not support.
+    ///
+    /// This holds the `authMode` of the page.
+    #[error("unsupported login step \"{0}\"")]
+    UnsupportedStep(String),
+
+    /// The verification code is wrong
+    #[error("invalid verification code")]
+    InvalidVerificationCode,
+
+    /// DeviantArt returned some other error message
+    #[error("{0}")]
+    Other(String),
+}
+
+impl LoginFailure {
+    /// Get the reason that a login page reports for a failure, if there is one.
+    pub(crate) fn from_page(page: &ScrapedWebPageInfo) -> Option<Self> {
+        let login = page.login.as_ref()?;
+
+        let error_type = login.error_type.to_ascii_lowercase();
+        let general_error = login.general_error.to_ascii_lowercase();
+        if error_type.contains("lock") || general_error.contains("locked") {
+            Some(Self::AccountLocked)
+        } else if login.show_captcha {
+            Some(Self::CaptchaRequired)
+        } else if login.username_invalid {
+            Some(Self::UnknownUsername)
+        } else if login.password_invalid {
+            Some(Self::WrongPassword)
+        } else if login.tfa_verification_code_invalid || login.tfa_recovery_code_invalid {
+            Some(Self::InvalidVerificationCode)
+        } else if !login.general_error.is_empty() {
+            Some(Self::Other(login.general_error.clone()))
+        } else {
+            None
+        }
+    }
+}
+
+/// A kind of verification code that DeviantArt can ask for while logging in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationKind {
+    /// A code sent by email
+    Email,
+
+    /// A code from a 2FA authenticator app
+    TwoFactor,
+}
+
+impl VerificationKind {
+    /// Get the verification that a login page asks for, if there is one.
+    fn from_page(page: &ScrapedWebPageInfo) -> Option<Self> {
+        let auth_mode = page.auth_mode.as_deref()?.to_ascii_lowercase();
+        if auth_mode.contains("tfa") || auth_mode.contains("2fa") {
+            Some(Self::TwoFactor)
+        } else if auth_mode.contains("verif") || auth_mode.contains("email") {
+            Some(Self::Email)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for VerificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Email => "email".fmt(f),
+            Self::TwoFactor => "2FA".fmt(f),
+        }
+    }
+}
+
+/// A login that is waiting for a verification code.
+///
+/// Pass this to [`Client::submit_verification_code`] to finish logging in.
+/// If the code is wrong, the same pending login can be used to try another code.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    /// The kind of verification that is needed
+    pub kind: VerificationKind,
+
+    /// The tokens of the verification page
+    csrf_token: Box<str>,
+    lu_token: Box<str>,
+    lu_token2: Box<str>,
+}
+
+impl PendingLogin {
+    /// Make a pending login from a page that asks for a verification code.
+    ///
+    /// Returns `None` if the page does not ask for one.
+    fn from_page(page: &ScrapedWebPageInfo) -> Result<Option<Self>, Error> {
+        let Some(kind) = VerificationKind::from_page(page) else {
+            return Ok(None);
+        };
+        let csrf_token = page
+            .csrf_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "csrfToken" })?;
+        let lu_token = page
+            .lu_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "luToken" })?;
+        let lu_token2 = page
+            .lu_token2
+            .as_deref()
+            .ok_or(Error::MissingField { name: "luToken2" })?;
+
+        Ok(Some(Self {
+            kind,
+            csrf_token: csrf_token.into(),
+            lu_token: lu_token.into(),
+            lu_token2: lu_token2.into(),
+        }))
+    }
+}
+
+/// The result of [`Client::start_login`].
+#[derive(Debug)]
+pub enum LoginStep {
+    /// The client is logged in.
+    LoggedIn,
+
+    /// A verification code must be submitted to finish logging in.
+    VerificationRequired(PendingLogin),
+}
+
+/// Get the `challenge` form value for a form posted from the given page.
+fn challenge_value(page: &ScrapedWebPageInfo) -> &'static str {
+    if VerificationKind::from_page(page).is_some() {
+        VERIFICATION_CHALLENGE
+    } else {
+        NO_CHALLENGE
+    }
+}
+
+impl Client {
+    /// Login to get access to more results from apis.
+    ///
+    /// This will also clean the cookie jar.
+    /// Use [`Client::start_login`] instead to handle accounts that need a verification code.
+    pub async fn login(&self, username: &str, password: &str) -> Result<(), Error> {
+        match self.start_login(username, password).await? {
+            LoginStep::LoggedIn => Ok(()),
+            LoginStep::VerificationRequired(pending) => {
+                Err(LoginFailure::VerificationRequired(pending.kind).into())
+            }
+        }
+    }
+
+    /// Start logging in.
+    ///
+    /// This will also clean the cookie jar.
+    /// If DeviantArt asks for a verification code,
+    /// the login can be finished with [`Client::submit_verification_code`].
+    pub async fn start_login(&self, username: &str, password: &str) -> Result<LoginStep, Error> {
+        // Clean the jar of expired cookies
+        {
+            let mut cookie_store = self.cookie_store.lock().expect("cookie store is poisoned");
+
+            // We need to allocate here as the cookie_store iter cannot be alive when we try to remove items from the cookie store.
+            let to_remove: Vec<_> = cookie_store
+                .iter_any()
+                .filter(|cookie| cookie.is_expired())
+                .map(|cookie| {
+                    let domain = cookie.domain().unwrap_or("");
+                    let name = cookie.name();
+                    let path = cookie.path().unwrap_or("");
+
+                    (domain.to_string(), name.to_string(), path.to_string())
+                })
+                .collect();
+
+            for (domain, name, path) in to_remove {
+                cookie_store.remove(&domain, &name, &path);
+            }
+        }
+
+        // Initial req to login page.
+        let login_url = self.endpoints.www_url("users/login")?;
+        let login_page = self.scrape_webpage_inner(login_url.as_str()).await?;
+        let login_page_csrf_token = login_page
+            .csrf_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "csrfToken" })?;
+        let login_page_lu_token = login_page
+            .lu_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "luToken" })?;
+
+        // Get the password input page.
+        // The username and password inputs are on different pages.
+        let password_page = self
+            .post_login_form(
+                self.endpoints.www_url("_sisu/do/step2")?,
+                &[
+                    ("referer", login_url.as_str()),
+                    ("referer_type", ""),
+                    ("csrf_token", login_page_csrf_token),
+                    ("challenge", challenge_value(&login_page)),
+                    ("lu_token", login_page_lu_token),
+                    ("username", username),
+                    ("remember", "on"),
+                ],
+            )
+            .await?;
+        if let Some(failure) = LoginFailure::from_page(&password_page) {
+            return Err(failure.into());
+        }
+        let password_page_csrf_token = password_page
+            .csrf_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "csrfToken" })?;
+        let password_page_lu_token = password_page
+            .lu_token
+            .as_deref()
+            .ok_or(Error::MissingField { name: "luToken" })?;
+        let password_page_lu_token2 = password_page
+            .lu_token2
+            .as_deref()
+            .ok_or(Error::MissingField { name: "luToken2" })?;
+
+        // Submit password
+        let signin_url = self.endpoints.www_url("_sisu/do/signin")?;
+        let signin_page = self
+            .post_login_form(
+                signin_url.clone(),
+                &[
+                    ("referer", signin_url.as_str()),
+                    ("referer_type", ""),
+                    ("csrf_token", password_page_csrf_token),
+                    ("challenge", challenge_value(&password_page)),
+                    ("lu_token", password_page_lu_token),
+                    ("lu_token2", password_page_lu_token2),
+                    ("username", ""),
+                    ("password", password),
+                    ("remember", "on"),
+                ],
+            )
+            .await?;
+        if signin_page.is_logged_in() {
+            self.save_session_or_emit().await;
+            return Ok(LoginStep::LoggedIn);
+        }
+
+        if let Some(failure) = LoginFailure::from_page(&signin_page) {
+            return Err(failure.into());
+        }
+
+        if let Some(pending) = PendingLogin::from_page(&signin_page)? {
+            return Ok(LoginStep::VerificationRequired(pending));
+        }
+
+        Err(unexpected_login_page(signin_page))
+    }
+
+    /// Finish a login by submitting the verification code that DeviantArt asked for.
+    ///
+    /// If the code is rejected, `pending` is updated so that another code can be submitted.
+    pub async fn submit_verification_code(
+        &self,
+        pending: &mut PendingLogin,
+        code: &str,
+    ) -> Result<(), Error> {
+        let verify_url = self.endpoints.www_url("_sisu/do/verify")?;
+        let verify_page = self
+            .post_login_form(
+                verify_url.clone(),
+                &[
+                    ("referer", verify_url.as_str()),
+                    ("referer_type", ""),
+                    ("csrf_token", &pending.csrf_token),
+                    ("challenge", VERIFICATION_CHALLENGE),
+                    ("lu_token", &pending.lu_token),
+                    ("lu_token2", &pending.lu_token2),
+                    ("code", code),
+                    ("remember", "on"),
+                ],
+            )
+            .await?;
+        if verify_page.is_logged_in() {
+            self.save_session_or_emit().await;
+            return Ok(());
+        }
+
+        // The page asks for a code again, so the old tokens are used up.
+        let next_pending = PendingLogin::from_page(&verify_page)?;
+        let failure = LoginFailure::from_page(&verify_page);
+        if let Some(next_pending) = next_pending {
+            *pending = next_pending;
+            return Err(failure
+                .unwrap_or(LoginFailure::InvalidVerificationCode)
+                .into());
+        }
+
+        if let Some(failure) = failure {
+            return Err(failure.into());
+        }
+
+        Err(unexpected_login_page(verify_page))
+    }
+
+    /// Post a login form and scrape the resulting page.
+    async fn post_login_form(
+        &self,
+        url: Url,
+        form: &[(&str, &str)],
+    ) -> Result<ScrapedWebPageInfo, Error> {
+        let text = self.send(self.client.post(url).form(form)).await?;
+        let page =
+            tokio::task::spawn_blocking(move || ScrapedWebPageInfo::from_html_str(&text)).await??;
+
+        Ok(page)
+    }
+}
+
+/// Get the error for a login page that is neither logged in nor reports a failure.
+fn unexpected_login_page(page: ScrapedWebPageInfo) -> Error {
+    match page.auth_mode {
+        Nullable::Value(auth_mode) if auth_mode != UNKNOWN_AUTH_MODE => {
+            LoginFailure::UnsupportedStep(auth_mode).into()
+        }
+        _ => Error::SignInFailed,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LOGIN_WEBPAGE: &str = include_str!("../../test_data/login_webpage.json");
+
+    /// Parse the login page with some login state fields changed.
+    fn parse_login_page(changes: &[(&str, serde_json::Value)]) -> ScrapedWebPageInfo {
+        let mut page: serde_json::Value =
+            serde_json::from_str(LOGIN_WEBPAGE).expect("failed to parse login page");
+        for (key, value) in changes {
+            page["login"][*key] = value.clone();
+        }
+        serde_json::from_value(page).expect("failed to parse login page")
+    }
+
+    #[test]
+    fn login_failure_from_page() {
+        let page = parse_login_page(&[]);
+        assert_eq!(LoginFailure::from_page(&page), None);
+
+        let page = parse_login_page(&[("passwordInvalid", true.into())]);
+        assert_eq!(
+            LoginFailure::from_page(&page),
+            Some(LoginFailure::WrongPassword)
+        );
+
+        let page = parse_login_page(&[("usernameInvalid", true.into())]);
+        assert_eq!(
+            LoginFailure::from_page(&page),
+            Some(LoginFailure::UnknownUsername)
+        );
+
+        let page = parse_login_page(&[("showCaptcha", true.into())]);
+        assert_eq!(
+            LoginFailure::from_page(&page),
+            Some(LoginFailure::CaptchaRequired)
+        );
+
+        let page = parse_login_page(&[
+            ("generalError", "This account has been locked.".into()),
+            ("errorType", "account_locked".into()),
+        ]);
+        assert_eq!(
+            LoginFailure::from_page(&page),
+            Some(LoginFailure::AccountLocked)
+        );
+    }
+
+    #[test]
+    fn verification_kind_from_page() {
+        let mut page = parse_login_page(&[]);
+        assert_eq!(VerificationKind::from_page(&page), None);
+        assert_eq!(challenge_value(&page), NO_CHALLENGE);
+
+        page.auth_mode = Nullable::Value("tfa".into());
+        assert_eq!(
+            VerificationKind::from_page(&page),
+            Some(VerificationKind::TwoFactor)
+        );
+        assert_eq!(challenge_value(&page), VERIFICATION_CHALLENGE);
+
+        page.auth_mode = Nullable::Value("email_verification".into());
+        assert_eq!(
+            VerificationKind::from_page(&page),
+            Some(VerificationKind::Email)
+        );
+        assert_eq!(challenge_value(&page), VERIFICATION_CHALLENGE);
+
+        page.auth_mode = Nullable::Value("passkey".into());
+        assert_eq!(VerificationKind::from_page(&page), None);
+    }
+}
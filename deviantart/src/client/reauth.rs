@@ -0,0 +1,138 @@
+use crate::Client;
+use crate::ClientEvent;
+use cookie_store::CookieStore;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use url::Url;
+
+/// A callback that is called with the cookie store after logging in.
+type LoginCallback = Arc<dyn Fn(&CookieStore) + Send + Sync>;
+
+/// A username and password.
+#[derive(Clone)]
+pub struct Credentials {
+    /// The username
+    pub username: String,
+
+    /// The password
+    pub password: String,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A provider of credentials, used to log a [`Client`] back in when its session expires.
+///
+/// When a scraped page shows that the client is logged out,
+/// or an api request says that logging in is required,
+/// the client logs in once with these credentials and retries the request.
+#[derive(Clone)]
+pub struct CredentialProvider {
+    /// The credential callback
+    get_credentials: Arc<dyn Fn() -> Option<Credentials> + Send + Sync>,
+
+    /// A callback to persist cookies after logging in
+    on_login: Option<LoginCallback>,
+
+    /// The number of times this provider has been used to log in.
+    generation: Arc<AtomicU64>,
+
+    /// A lock to make sure that concurrent requests only log in once.
+    lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl CredentialProvider {
+    /// Make a new [`CredentialProvider`] from a function.
+    ///
+    /// The function may return `None` to skip logging in.
+    pub fn new<F>(func: F) -> Self
+    where
+        F: Fn() -> Option<Credentials> + Send + Sync + 'static,
+    {
+        Self {
+            get_credentials: Arc::new(func),
+            on_login: None,
+            generation: Arc::new(AtomicU64::new(0)),
+            lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Set a callback that is called with the refreshed cookies after logging in.
+    ///
+    /// Use this to save the cookies.
+    pub fn on_login<F>(mut self, func: F) -> Self
+    where
+        F: Fn(&CookieStore) + Send + Sync + 'static,
+    {
+        self.on_login = Some(Arc::new(func));
+        self
+    }
+}
+
+impl std::fmt::Debug for CredentialProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialProvider")
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Get the number of times the credential provider has logged in, if there is one.
+    ///
+    /// Get this before a request, and pass it to [`Client::reauthenticate`] if the request shows a logged out state.
+    pub(crate) fn get_reauth_generation(&self) -> Option<u64> {
+        let provider = self.credential_provider.as_ref()?;
+        Some(provider.generation.load(Ordering::SeqCst))
+    }
+
+    /// Log in again after a request to `url` showed a logged out state.
+    ///
+    /// This returns `true` if the request should be retried.
+    /// A failed login is reported as a [`ClientEvent::ReauthenticationFailed`],
+    /// so that the caller can return the logged out result it already has.
+    pub(crate) async fn reauthenticate(&self, generation: Option<u64>, url: &Url) -> bool {
+        let (Some(provider), Some(generation)) = (self.credential_provider.as_ref(), generation)
+        else {
+            return false;
+        };
+
+        let _guard = provider.lock.lock().await;
+        if provider.generation.load(Ordering::SeqCst) != generation {
+            // Another request already logged in again while we were waiting.
+            return true;
+        }
+
+        let Some(credentials) = (provider.get_credentials)() else {
+            return false;
+        };
+        if let Err(error) = self
+            .login(&credentials.username, &credentials.password)
+            .await
+        {
+            self.emit_event(ClientEvent::ReauthenticationFailed { url, error: &error });
+            return false;
+        }
+        provider.generation.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(on_login) = provider.on_login.as_ref() {
+            // Clone the store so the callback can use the client without deadlocking.
+            let cookie_store = self
+                .cookie_store
+                .lock()
+                .expect("cookie store is poisoned")
+                .clone();
+            on_login(&cookie_store);
+        }
+        self.emit_event(ClientEvent::Reauthenticated { url });
+
+        true
+    }
+}
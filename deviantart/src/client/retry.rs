@@ -0,0 +1,349 @@
+use crate::BrowseStreamItem;
+use crate::Error;
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use time::OffsetDateTime;
+use time::PrimitiveDateTime;
+use time::macros::format_description;
+use url::Url;
+
+/// A policy for retrying failed requests.
+///
+/// Requests are retried on connection errors, timeouts, rate limits, and transient server errors.
+/// Only GET and HEAD requests are retried,
+/// so forms like the login form are never submitted twice.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The max number of times a request will be retried.
+    ///
+    /// A value of 0 disables retries.
+    pub max_retries: u32,
+
+    /// The delay before the first retry.
+    ///
+    /// This doubles for every following retry.
+    pub initial_backoff: Duration,
+
+    /// The max delay between retries.
+    ///
+    /// This also limits delays requested by the server with a `Retry-After` header.
+    /// A longer request is reported with a [`ClientEvent::RetryAfterCapped`].
+    pub max_backoff: Duration,
+
+    /// Whether to randomize delays, to avoid many clients retrying at the same time.
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Make a new [`RetryPolicy`] with the default settings.
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            jitter: true,
+        }
+    }
+
+    /// Make a [`RetryPolicy`] that never retries.
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::new()
+        }
+    }
+
+    /// Get the delay before the given retry, starting from 0.
+    pub(crate) fn get_backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // Wait somewhere between half and all of the backoff.
+            let half = backoff / 2;
+            half + half.mul_f64(fastrand::f64())
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A limiter for the rate of requests.
+///
+/// This is shared between all clones of a [`Client`](crate::Client).
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// The min time between requests.
+    min_interval: Duration,
+
+    /// The earliest time that the next request may be sent.
+    next_request: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Make a new [`RateLimiter`] that waits at least `min_interval` between requests.
+    ///
+    /// A `min_interval` of 0 only delays requests when the server asks for it.
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserve a time slot for a request, returning the delay until that slot.
+    pub(crate) fn reserve(&self) -> Duration {
+        let now = Instant::now();
+        let mut next_request = self.next_request.lock().expect("rate limiter is poisoned");
+        let slot = (*next_request).max(now);
+        *next_request = slot + self.min_interval;
+
+        slot - now
+    }
+
+    /// Delay all requests until the given duration has passed.
+    pub fn pause(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut next_request = self.next_request.lock().expect("rate limiter is poisoned");
+        if *next_request < until {
+            *next_request = until;
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+/// An event emitted by a [`Client`](crate::Client) while it is sending requests.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ClientEvent<'a> {
+    /// A request is being delayed by the [`RateLimiter`].
+    Throttled {
+        /// The request url
+        url: &'a Url,
+
+        /// The time until the request is sent
+        delay: Duration,
+    },
+
+    /// The server rate limited a request.
+    ///
+    /// All requests are paused until the delay passes.
+    RateLimited {
+        /// The request url
+        url: &'a Url,
+
+        /// The time until the request is retried
+        delay: Duration,
+    },
+
+    /// The session expired, and the client logged in again with its credential provider.
+    Reauthenticated {
+        /// The url of the request that showed that the session expired
+        url: &'a Url,
+    },
+
+    /// The session expired, and logging in again with the credential provider failed.
+    ///
+    /// The request returns its logged out result.
+    ReauthenticationFailed {
+        /// The url of the request that showed that the session expired
+        url: &'a Url,
+
+        /// The login error
+        error: &'a Error,
+    },
+
+    /// Items of a search page that are not deviations were skipped.
+    ///
+    /// `SearchCursor::current_other_items` also returns these.
+    SkippedStreamItems {
+        /// The skipped items
+        items: &'a [BrowseStreamItem],
+    },
+
+    /// Saving the session to the session store failed.
+    ///
+    /// Sessions are saved automatically after logging in and out,
+    /// and a failed save does not fail those calls.
+    SessionSaveFailed {
+        /// The error
+        error: &'a Error,
+    },
+
+    /// The server asked for a longer `Retry-After` delay than the retry policy allows.
+    ///
+    /// The request is retried after [`RetryPolicy::max_backoff`] instead.
+    RetryAfterCapped {
+        /// The request url
+        url: &'a Url,
+
+        /// The delay the server asked for
+        requested: Duration,
+
+        /// The delay that will be used
+        delay: Duration,
+    },
+
+    /// A request failed and will be retried.
+    Retry {
+        /// The request url
+        url: &'a Url,
+
+        /// The response status, if a response was received.
+        status: Option<StatusCode>,
+
+        /// The retry number, starting from 1.
+        retry: u32,
+
+        /// The time until the request is retried
+        delay: Duration,
+    },
+}
+
+/// A callback for [`ClientEvent`]s.
+#[derive(Clone)]
+pub struct EventHandler(Arc<dyn Fn(&ClientEvent<'_>) + Send + Sync>);
+
+impl EventHandler {
+    /// Make a new [`EventHandler`] from a function.
+    pub fn new<F>(func: F) -> Self
+    where
+        F: Fn(&ClientEvent<'_>) + Send + Sync + 'static,
+    {
+        Self(Arc::new(func))
+    }
+
+    /// Call this handler with an event.
+    pub(crate) fn emit(&self, event: &ClientEvent<'_>) {
+        (self.0)(event)
+    }
+}
+
+impl std::fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EventHandler").finish_non_exhaustive()
+    }
+}
+
+/// Returns `true` if a response with the given status should be retried.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Returns `true` if a request with the given method may be sent again.
+///
+/// Other methods may have side effects, like submitting the login form.
+pub(crate) fn is_retryable_method(method: &reqwest::Method) -> bool {
+    matches!(*method, reqwest::Method::GET | reqwest::Method::HEAD)
+}
+
+/// Returns `true` if a request that failed with the given error should be retried.
+pub(crate) fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Parse the `Retry-After` header.
+///
+/// This may be either a number of seconds or an HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let format = format_description!(
+        "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+    );
+    let date = PrimitiveDateTime::parse(value, format).ok()?.assume_utc();
+    let delay = date - OffsetDateTime::now_utc();
+
+    // A date in the past means that we can retry now.
+    Some(delay.try_into().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::new()
+        };
+        assert_eq!(policy.get_backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.get_backoff(3), Duration::from_secs(8));
+        assert_eq!(policy.get_backoff(100), policy.max_backoff);
+
+        let policy = RetryPolicy::new();
+        for retry in 0..10 {
+            let backoff = policy.get_backoff(retry);
+            assert!(backoff <= policy.max_backoff);
+            assert!(backoff >= policy.initial_backoff / 2);
+        }
+    }
+
+    #[test]
+    fn only_idempotent_methods_are_retried() {
+        assert!(is_retryable_method(&reqwest::Method::GET));
+        assert!(is_retryable_method(&reqwest::Method::HEAD));
+        assert!(!is_retryable_method(&reqwest::Method::POST));
+    }
+
+    #[test]
+    fn retry_after() {
+        let mut headers = HeaderMap::new();
+        assert!(parse_retry_after(&headers).is_none());
+
+        headers.insert(reqwest::header::RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(30)));
+
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn rate_limiter_spaces_requests() {
+        let rate_limiter = RateLimiter::new(Duration::from_secs(10));
+        assert_eq!(rate_limiter.reserve(), Duration::ZERO);
+        assert!(rate_limiter.reserve() > Duration::from_secs(9));
+
+        let rate_limiter = RateLimiter::default();
+        rate_limiter.pause(Duration::from_secs(30));
+        assert!(rate_limiter.reserve() > Duration::from_secs(29));
+    }
+}
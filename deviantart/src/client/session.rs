@@ -0,0 +1,111 @@
+use crate::Client;
+use crate::Error;
+use crate::Nullable;
+use crate::UserId;
+use crate::types::scraped_webpage_info::PublicSession;
+use cookie_store::CookieExpiration;
+use time::OffsetDateTime;
+
+/// The names of the cookies that keep a client logged in.
+const LOGIN_COOKIE_NAMES: &[&str] = &["auth", "auth_secure", "userinfo"];
+
+/// Info about the session of a [`Client`].
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Whether the client is logged in
+    pub is_logged_in: bool,
+
+    /// The username, if logged in.
+    pub username: Option<String>,
+
+    /// The user id, if logged in.
+    pub user_id: Option<UserId>,
+
+    /// The account type, like "regular" or "admin", if logged in.
+    pub account_type: Option<String>,
+
+    /// Whether the user has a core membership
+    pub has_core_membership: bool,
+
+    /// Whether mature content is shown
+    pub show_mature_content: bool,
+
+    /// When the login cookies expire.
+    ///
+    /// This is `None` if there are no login cookies or they only last until the session ends.
+    pub cookie_expiry: Option<OffsetDateTime>,
+}
+
+impl Session {
+    /// Make a new [`Session`] from a public session.
+    fn new(public_session: &PublicSession, cookie_expiry: Option<OffsetDateTime>) -> Self {
+        let is_logged_in = public_session.is_logged_in;
+        let user = public_session.user.as_ref().filter(|_| is_logged_in);
+
+        Self {
+            is_logged_in,
+            username: user.map(|user| user.username.clone()),
+            user_id: user.map(|user| user.user_id),
+            account_type: user.and_then(|user| user.kind.as_ref().cloned()),
+            has_core_membership: public_session.has_core_membership == Nullable::Value(true),
+            show_mature_content: public_session.show_mature_content == Nullable::Value(true),
+            cookie_expiry,
+        }
+    }
+}
+
+impl Client {
+    /// Get info about the current session.
+    ///
+    /// This makes a request to the home page.
+    pub async fn session(&self) -> Result<Session, Error> {
+        let scraped_webpage = self
+            .scrape_webpage_inner(self.endpoints.www.as_str())
+            .await?;
+
+        Ok(Session::new(
+            &scraped_webpage.public_session,
+            self.get_cookie_expiry(),
+        ))
+    }
+
+    /// Log out, invalidating the session on the server and clearing the cookie store.
+    ///
+    /// The cookie store is cleared and saved even if the server request fails.
+    pub async fn logout(&self) -> Result<(), Error> {
+        let result = async {
+            let scraped_webpage = self
+                .scrape_webpage_inner(self.endpoints.www.as_str())
+                .await?;
+            let request = self
+                .client
+                .post(self.endpoints.www_url("users/logout")?)
+                .form(&[("csrf_token", scraped_webpage.config.csrf_token.as_str())]);
+            self.send(request).await?;
+
+            Ok(())
+        }
+        .await;
+
+        self.cookie_store
+            .lock()
+            .expect("cookie store is poisoned")
+            .clear();
+        self.save_session_or_emit().await;
+
+        result
+    }
+
+    /// Get the earliest expiry time of the login cookies.
+    fn get_cookie_expiry(&self) -> Option<OffsetDateTime> {
+        let cookie_store = self.cookie_store.lock().expect("cookie store is poisoned");
+        cookie_store
+            .iter_unexpired()
+            .filter(|cookie| LOGIN_COOKIE_NAMES.contains(&cookie.name()))
+            .filter_map(|cookie| match cookie.expires {
+                CookieExpiration::AtUtc(expires) => Some(expires),
+                CookieExpiration::SessionEnd => None,
+            })
+            .min()
+    }
+}
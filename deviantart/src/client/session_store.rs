@@ -0,0 +1,394 @@
+use crate::Client;
+use crate::ClientEvent;
+use crate::Error;
+use crate::WrapBoxError;
+use reqwest::header::HeaderValue;
+use reqwest_cookie_store::CookieStoreMutex;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use url::Url;
+
+/// How long to wait after cookies change before saving the session automatically.
+///
+/// Responses often come in bursts, so this saves once per burst instead of after every response.
+const SESSION_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// A place to persist the cookies of a [`Client`] between runs.
+///
+/// Stores work with the serialized cookie store,
+/// so that they can be layered, like an [`EncryptedSessionStore`] over a [`FileSessionStore`].
+pub trait SessionStore: std::fmt::Debug + Send + Sync {
+    /// Load the saved session, or `None` if nothing was saved.
+    fn load(&self) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Save a session, replacing the saved session.
+    fn save(&self, data: &[u8]) -> Result<(), Error>;
+}
+
+/// A [`SessionStore`] that saves to a file.
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    /// The file path
+    path: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Make a new [`FileSessionStore`] that saves to the given path.
+    ///
+    /// The parent directories are created when saving if they do not exist.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self) -> Result<Option<Vec<u8>>, Error> {
+        match std::fs::read(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save(&self, data: &[u8]) -> Result<(), Error> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first, so a crash cannot leave a half-written session.
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+
+        // The session holds login cookies, so only the owner may read it.
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temp_path)?;
+        #[cfg(unix)]
+        {
+            // The mode only applies to new files, so fix up a leftover temporary file too.
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        std::io::Write::write_all(&mut file, data)?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// A [`SessionStore`] that keeps the session in memory.
+///
+/// This is useful for tests, and for sharing a session between clients that are built at different times.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    /// The saved session
+    data: Mutex<Option<Vec<u8>>>,
+}
+
+impl MemorySessionStore {
+    /// Make a new empty [`MemorySessionStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(&self) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.data.lock().expect("session store is poisoned").clone())
+    }
+
+    fn save(&self, data: &[u8]) -> Result<(), Error> {
+        *self.data.lock().expect("session store is poisoned") = Some(data.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "encrypted-session-store")]
+pub use self::encrypted::EncryptedSessionStore;
+
+#[cfg(feature = "encrypted-session-store")]
+mod encrypted {
+    use super::SessionStore;
+    use crate::Error;
+    use chacha20poly1305::KeyInit;
+    use chacha20poly1305::XChaCha20Poly1305;
+    use chacha20poly1305::XNonce;
+    use chacha20poly1305::aead::Aead;
+    use chacha20poly1305::aead::AeadCore;
+    use chacha20poly1305::aead::OsRng;
+    use chacha20poly1305::aead::rand_core::RngCore;
+
+    /// The start of every encrypted session, followed by a format version.
+    const MAGIC: &[u8] = b"DASESSION\x01";
+    const SALT_LEN: usize = 16;
+    const NONCE_LEN: usize = 24;
+    const KEY_LEN: usize = 32;
+
+    /// A [`SessionStore`] that encrypts the session before passing it to another store.
+    ///
+    /// The key is derived from a passphrase with Argon2,
+    /// and the session is encrypted with XChaCha20-Poly1305.
+    pub struct EncryptedSessionStore<S> {
+        /// The store for the encrypted session
+        inner: S,
+
+        /// The passphrase
+        passphrase: String,
+    }
+
+    impl<S> EncryptedSessionStore<S> {
+        /// Make a new [`EncryptedSessionStore`] that saves to `inner`, encrypted with `passphrase`.
+        pub fn new(inner: S, passphrase: impl Into<String>) -> Self {
+            Self {
+                inner,
+                passphrase: passphrase.into(),
+            }
+        }
+
+        /// Derive the encryption key for the given salt.
+        fn derive_cipher(&self, salt: &[u8]) -> Result<XChaCha20Poly1305, Error> {
+            let mut key = [0; KEY_LEN];
+            argon2::Argon2::default()
+                .hash_password_into(self.passphrase.as_bytes(), salt, &mut key)
+                .map_err(|_error| Error::SessionEncryption)?;
+            Ok(XChaCha20Poly1305::new(&key.into()))
+        }
+    }
+
+    impl<S> std::fmt::Debug for EncryptedSessionStore<S>
+    where
+        S: std::fmt::Debug,
+    {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("EncryptedSessionStore")
+                .field("inner", &self.inner)
+                .field("passphrase", &"<redacted>")
+                .finish()
+        }
+    }
+
+    impl<S> SessionStore for EncryptedSessionStore<S>
+    where
+        S: SessionStore,
+    {
+        fn load(&self) -> Result<Option<Vec<u8>>, Error> {
+            let Some(data) = self.inner.load()? else {
+                return Ok(None);
+            };
+
+            let data = data.strip_prefix(MAGIC).ok_or(Error::SessionEncryption)?;
+            if data.len() < SALT_LEN + NONCE_LEN {
+                return Err(Error::SessionEncryption);
+            }
+            let (salt, data) = data.split_at(SALT_LEN);
+            let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+
+            let cipher = self.derive_cipher(salt)?;
+            let data = cipher
+                .decrypt(XNonce::from_slice(nonce), ciphertext)
+                .map_err(|_error| Error::SessionEncryption)?;
+
+            Ok(Some(data))
+        }
+
+        fn save(&self, data: &[u8]) -> Result<(), Error> {
+            let mut salt = [0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+            let cipher = self.derive_cipher(&salt)?;
+            let ciphertext = cipher
+                .encrypt(&nonce, data)
+                .map_err(|_error| Error::SessionEncryption)?;
+
+            let mut output =
+                Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+            output.extend(MAGIC);
+            output.extend(salt);
+            output.extend(nonce);
+            output.extend(ciphertext);
+
+            self.inner.save(&output)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::MemorySessionStore;
+
+        #[test]
+        fn encrypted_session_store() {
+            let store = EncryptedSessionStore::new(MemorySessionStore::new(), "hunter2");
+            assert!(store.load().expect("failed to load").is_none());
+
+            store.save(b"secret cookies").expect("failed to save");
+            let raw = store
+                .inner
+                .load()
+                .expect("failed to load")
+                .expect("missing session");
+            assert!(!raw.windows(7).any(|window| window == b"cookies"));
+            assert_eq!(
+                store.load().expect("failed to load").as_deref(),
+                Some(&b"secret cookies"[..])
+            );
+
+            let wrong_store = EncryptedSessionStore::new(MemorySessionStore::new(), "hunter3");
+            wrong_store.inner.save(&raw).expect("failed to save");
+            assert!(matches!(wrong_store.load(), Err(Error::SessionEncryption)));
+        }
+    }
+}
+
+/// A cookie provider that records when cookies are set.
+#[derive(Debug)]
+pub(crate) struct TrackingCookieStore {
+    /// The cookie store
+    pub cookie_store: Arc<CookieStoreMutex>,
+
+    /// Set when the server sets cookies
+    pub changed: Arc<AtomicBool>,
+}
+
+impl reqwest::cookie::CookieStore for TrackingCookieStore {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        self.cookie_store.set_cookies(cookie_headers, url);
+        self.changed.store(true, Ordering::SeqCst);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.cookie_store.cookies(url)
+    }
+}
+
+/// The state of automatic session saves, shared between clones of a [`Client`].
+#[derive(Debug, Default)]
+pub(crate) struct SessionSaveState {
+    /// Set while an automatic save is waiting to run
+    scheduled: AtomicBool,
+
+    /// Held while saving, so that saves do not overlap and the newest cookies are written last
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl Client {
+    /// Load the session from the session store into the cookie store, if one was saved.
+    pub(crate) fn load_session(&self) -> Result<(), Error> {
+        let Some(session_store) = self.session_store.as_ref() else {
+            return Ok(());
+        };
+        let Some(data) = session_store.load()? else {
+            return Ok(());
+        };
+
+        let new_cookie_store = cookie_store::serde::json::load(data.as_slice())
+            .map_err(|e| Error::CookieStore(WrapBoxError(e)))?;
+        *self.cookie_store.lock().expect("cookie store is poisoned") = new_cookie_store;
+
+        Ok(())
+    }
+
+    /// Save the cookie store to the session store, if there is one.
+    ///
+    /// This is done automatically after logging in and logging out,
+    /// and shortly after the server sets cookies.
+    pub async fn save_session(&self) -> Result<(), Error> {
+        let Some(session_store) = self.session_store.clone() else {
+            return Ok(());
+        };
+
+        let _guard = self.session_save.lock.lock().await;
+        let mut data = Vec::new();
+        {
+            let cookie_store = self.cookie_store.lock().expect("cookie store is poisoned");
+            cookie_store::serde::json::save(&cookie_store, &mut data)
+                .map_err(|e| Error::CookieStore(WrapBoxError(e)))?;
+        }
+        self.cookies_changed.store(false, Ordering::SeqCst);
+
+        tokio::task::spawn_blocking(move || session_store.save(&data)).await?
+    }
+
+    /// Save the cookie store to the session store if the server set cookies since the last save.
+    ///
+    /// Changed cookies are saved automatically after a short delay,
+    /// so this only needs to be called before exiting, to not lose the last changes.
+    pub async fn save_session_if_changed(&self) -> Result<(), Error> {
+        if self.session_store.is_none() || !self.cookies_changed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        self.save_session().await
+    }
+
+    /// Schedule an automatic save of the session if the server set cookies.
+    ///
+    /// The save runs after [`SESSION_SAVE_DELAY`], and at most one save is scheduled at a time.
+    pub(crate) fn schedule_session_save(&self) {
+        if self.session_store.is_none() || !self.cookies_changed.load(Ordering::SeqCst) {
+            return;
+        }
+        if self.session_save.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let client = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(SESSION_SAVE_DELAY).await;
+            client.session_save.scheduled.store(false, Ordering::SeqCst);
+            if client.cookies_changed.load(Ordering::SeqCst) {
+                client.save_session_or_emit().await;
+            }
+        });
+    }
+
+    /// Save the cookie store to the session store, reporting a failure as a [`ClientEvent`].
+    ///
+    /// This is used for automatic saves, so that a failed save does not fail the request that triggered it.
+    pub(crate) async fn save_session_or_emit(&self) {
+        if let Err(error) = self.save_session().await {
+            self.emit_event(ClientEvent::SessionSaveFailed { error: &error });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_session_store() {
+        let dir = std::env::temp_dir().join(format!("deviantart-session-{}", fastrand::u64(..)));
+        let store = FileSessionStore::new(dir.join("nested/session.json"));
+        assert!(store.load().expect("failed to load").is_none());
+
+        store.save(b"{}").expect("failed to save");
+        assert_eq!(
+            store.load().expect("failed to load").as_deref(),
+            Some(&b"{}"[..])
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let metadata =
+                std::fs::metadata(dir.join("nested/session.json")).expect("failed to get metadata");
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove session dir");
+    }
+}
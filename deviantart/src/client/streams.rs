@@ -0,0 +1,114 @@
+use crate::Client;
+use crate::ContinuableStream;
+use crate::Error;
+use crate::StreamPageResponse;
+use crate::StreamPosition;
+use crate::types::scraped_webpage_info::ItemStreamParams;
+use std::borrow::Cow;
+
+/// The number of items to fetch when a stream does not say how many it fetches at a time.
+const DEFAULT_ITEMS_PER_FETCH: u64 = 24;
+
+impl Client {
+    /// Fetch a page of a stream from its request endpoint.
+    ///
+    /// `stream_params` come from a stream on a scraped page,
+    /// and `position` comes from that stream or from the previous [`StreamPageResponse`].
+    pub async fn fetch_stream_page(
+        &self,
+        stream_params: &ItemStreamParams,
+        position: &StreamPosition,
+        limit: u64,
+        csrf_token: &str,
+    ) -> Result<StreamPageResponse, Error> {
+        let request_endpoint =
+            stream_params
+                .request_endpoint
+                .as_deref()
+                .ok_or(Error::StreamNotContinuable {
+                    stream_id: None,
+                    fetch_next_callback: None,
+                })?;
+
+        self.fetch_stream_page_inner(
+            request_endpoint,
+            &stream_params.query_pairs(),
+            position,
+            limit,
+            csrf_token,
+        )
+        .await
+    }
+
+    /// Fetch a page of a stream from a request endpoint and query params.
+    async fn fetch_stream_page_inner(
+        &self,
+        request_endpoint: &str,
+        request_params: &[(&str, Cow<'_, str>)],
+        position: &StreamPosition,
+        limit: u64,
+        csrf_token: &str,
+    ) -> Result<StreamPageResponse, Error> {
+        let mut url = self.endpoints.www_url(request_endpoint)?;
+        {
+            let mut query_pairs = url.query_pairs_mut();
+            for (key, value) in request_params.iter() {
+                query_pairs.append_pair(key, value);
+            }
+
+            match position {
+                StreamPosition::Offset(offset) => {
+                    query_pairs.append_pair("offset", itoa::Buffer::new().format(*offset));
+                }
+                StreamPosition::Cursor(cursor) => {
+                    query_pairs.append_pair("cursor", cursor);
+                }
+                StreamPosition::Page(page) => {
+                    query_pairs.append_pair("page", itoa::Buffer::new().format(*page));
+                }
+            }
+            query_pairs.append_pair("limit", itoa::Buffer::new().format(limit));
+            query_pairs.append_pair("csrf_token", csrf_token);
+        }
+
+        self.get_with_reauth(url.as_str(), |text| serde_json::from_str(text), |_| false)
+            .await
+    }
+
+    /// Fetch the page after the loaded items of a stream from a scraped page,
+    /// like a [`Stream`](crate::Stream) or a [`BrowsePageStream`](crate::types::scraped_webpage_info::BrowsePageStream).
+    ///
+    /// This returns `None` if the stream has no more items.
+    /// Streams without a request endpoint are only continued by page scripts,
+    /// so they return [`Error::StreamNotContinuable`].
+    pub async fn fetch_next_stream_page<S>(
+        &self,
+        stream: &S,
+        csrf_token: &str,
+    ) -> Result<Option<StreamPageResponse>, Error>
+    where
+        S: ContinuableStream + ?Sized,
+    {
+        let Some(position) = stream.next_position() else {
+            return Ok(None);
+        };
+        let request_endpoint =
+            stream
+                .request_endpoint()
+                .ok_or_else(|| Error::StreamNotContinuable {
+                    stream_id: stream.stream_id().map(String::from),
+                    fetch_next_callback: stream.fetch_next_callback().map(String::from),
+                })?;
+        let limit = stream.items_per_fetch().unwrap_or(DEFAULT_ITEMS_PER_FETCH);
+
+        self.fetch_stream_page_inner(
+            request_endpoint,
+            &stream.request_params(),
+            &position,
+            limit,
+            csrf_token,
+        )
+        .await
+        .map(Some)
+    }
+}
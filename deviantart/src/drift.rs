@@ -0,0 +1,412 @@
+#[cfg(feature = "serde-serialize")]
+use crate::ScrapedWebPageInfo;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// The type of a json value.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    /// null
+    Null,
+
+    /// true or false
+    Bool,
+
+    /// A number
+    Number,
+
+    /// A string
+    String,
+
+    /// An array
+    Array,
+
+    /// An object
+    Object,
+}
+
+impl JsonType {
+    /// Get the type of a json value.
+    pub fn of(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Null,
+            serde_json::Value::Bool(_) => Self::Bool,
+            serde_json::Value::Number(_) => Self::Number,
+            serde_json::Value::String(_) => Self::String,
+            serde_json::Value::Array(_) => Self::Array,
+            serde_json::Value::Object(_) => Self::Object,
+        }
+    }
+}
+
+impl std::fmt::Display for JsonType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The recorded shape of scraped pages, used to detect layout changes.
+///
+/// This maps each key path to the types that were seen at it.
+/// In paths, object keys are joined with `.` and array items are written as `[]`.
+/// Runs of 4 or more digits in keys, like entity ids, are replaced with `*`,
+/// so that pages with different entities share paths.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SchemaBaseline {
+    /// The types seen at each path
+    pub paths: BTreeMap<String, BTreeSet<JsonType>>,
+}
+
+impl SchemaBaseline {
+    /// Make a new empty [`SchemaBaseline`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the shape of a scraped page.
+    ///
+    /// This only sees the keys that the page types keep.
+    /// Use [`SchemaBaseline::record_value`] with [`Client::scrape_initial_state`](crate::Client::scrape_initial_state) to see every key.
+    #[cfg(feature = "serde-serialize")]
+    pub fn record(
+        &mut self,
+        scraped_webpage: &ScrapedWebPageInfo,
+    ) -> Result<(), serde_json::Error> {
+        self.record_value(&serde_json::to_value(scraped_webpage)?);
+        Ok(())
+    }
+
+    /// Record the shape of a json value.
+    pub fn record_value(&mut self, value: &serde_json::Value) {
+        for (path, types) in collect_paths(value) {
+            self.paths.entry(path).or_default().extend(types);
+        }
+    }
+
+    /// Compare a scraped page against this baseline.
+    ///
+    /// This only sees the keys that the page types keep.
+    /// Use [`SchemaBaseline::report_value`] with [`Client::scrape_initial_state`](crate::Client::scrape_initial_state) to see every key.
+    /// Missing optional fields are not serialized, so they are reported missing like other keys.
+    #[cfg(feature = "serde-serialize")]
+    pub fn report(
+        &self,
+        scraped_webpage: &ScrapedWebPageInfo,
+    ) -> Result<DriftReport, serde_json::Error> {
+        Ok(self.report_value(&serde_json::to_value(scraped_webpage)?))
+    }
+
+    /// Compare a json value against this baseline.
+    ///
+    /// Keys are only reported missing if their parent is present,
+    /// as pages do not always have the same optional sections.
+    /// Nulls never count as a type mismatch, since pages use them for empty optional values.
+    pub fn report_value(&self, value: &serde_json::Value) -> DriftReport {
+        let paths = collect_paths(value);
+        let mut report = DriftReport::default();
+
+        for (path, found) in paths.iter() {
+            let Some(expected) = self.paths.get(path) else {
+                report.new_keys.push(path.clone());
+                continue;
+            };
+
+            let found_types = non_null_types(found);
+            let expected_types = non_null_types(expected);
+            let is_mismatch = !found_types.is_empty()
+                && !expected_types.is_empty()
+                && found_types.is_disjoint(&expected_types);
+            if is_mismatch {
+                report.type_mismatches.push(TypeMismatch {
+                    path: path.clone(),
+                    expected: expected.clone(),
+                    found: found.clone(),
+                });
+            }
+        }
+
+        for path in self.paths.keys() {
+            let parent_present = parent_path(path).is_none_or(|parent| paths.contains_key(parent));
+            if parent_present && !paths.contains_key(path) {
+                report.missing_keys.push(path.clone());
+            }
+        }
+
+        // Only report the top of a new subtree.
+        let new_keys = std::mem::take(&mut report.new_keys);
+        report.new_keys = new_keys
+            .iter()
+            .filter(|path| parent_path(path).is_none_or(|parent| self.paths.contains_key(parent)))
+            .cloned()
+            .collect();
+
+        report
+    }
+}
+
+/// The differences between a page and a [`SchemaBaseline`].
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct DriftReport {
+    /// Paths that are not in the baseline
+    pub new_keys: Vec<String>,
+
+    /// Paths in the baseline that are missing from the page
+    pub missing_keys: Vec<String>,
+
+    /// Paths whose types do not match the baseline
+    pub type_mismatches: Vec<TypeMismatch>,
+}
+
+impl DriftReport {
+    /// Returns `true` if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.new_keys.is_empty() && self.missing_keys.is_empty() && self.type_mismatches.is_empty()
+    }
+}
+
+impl std::fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no drift");
+        }
+
+        for path in self.new_keys.iter() {
+            writeln!(f, "+ {path}")?;
+        }
+        for path in self.missing_keys.iter() {
+            writeln!(f, "- {path}")?;
+        }
+        for mismatch in self.type_mismatches.iter() {
+            writeln!(
+                f,
+                "~ {}: expected {}, found {}",
+                mismatch.path,
+                join_types(&mismatch.expected),
+                join_types(&mismatch.found)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A path whose types do not match the baseline.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TypeMismatch {
+    /// The path
+    pub path: String,
+
+    /// The types in the baseline
+    pub expected: BTreeSet<JsonType>,
+
+    /// The types on the page
+    pub found: BTreeSet<JsonType>,
+}
+
+/// Collect the types at every path of a json value, not including the root.
+fn collect_paths(value: &serde_json::Value) -> BTreeMap<String, BTreeSet<JsonType>> {
+    let mut paths = BTreeMap::new();
+    let mut path = String::new();
+    collect_paths_inner(value, &mut path, &mut paths);
+    paths
+}
+
+fn collect_paths_inner(
+    value: &serde_json::Value,
+    path: &mut String,
+    paths: &mut BTreeMap<String, BTreeSet<JsonType>>,
+) {
+    let len = path.len();
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object.iter() {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                push_normalized_key(path, key);
+                paths
+                    .entry(path.clone())
+                    .or_default()
+                    .insert(JsonType::of(value));
+                collect_paths_inner(value, path, paths);
+                path.truncate(len);
+            }
+        }
+        serde_json::Value::Array(array) => {
+            path.push_str("[]");
+            for value in array.iter() {
+                paths
+                    .entry(path.clone())
+                    .or_default()
+                    .insert(JsonType::of(value));
+                collect_paths_inner(value, path, paths);
+            }
+            path.truncate(len);
+        }
+        _ => {}
+    }
+}
+
+/// Push a key, replacing runs of 4 or more digits with `*`.
+fn push_normalized_key(path: &mut String, key: &str) {
+    let mut rest = key;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let digits_len = rest[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - start);
+        path.push_str(&rest[..start]);
+        if digits_len >= 4 {
+            path.push('*');
+        } else {
+            path.push_str(&rest[start..start + digits_len]);
+        }
+        rest = &rest[start + digits_len..];
+    }
+    path.push_str(rest);
+}
+
+/// Get the parent of a path, or `None` if it is at the root.
+fn parent_path(path: &str) -> Option<&str> {
+    if let Some(parent) = path.strip_suffix("[]") {
+        return Some(parent);
+    }
+    path.rfind('.').map(|index| &path[..index])
+}
+
+/// Get the types that are not null.
+fn non_null_types(types: &BTreeSet<JsonType>) -> BTreeSet<JsonType> {
+    types
+        .iter()
+        .copied()
+        .filter(|json_type| *json_type != JsonType::Null)
+        .collect()
+}
+
+/// Join types with `|`.
+fn join_types(types: &BTreeSet<JsonType>) -> String {
+    let mut output = String::new();
+    for (i, json_type) in types.iter().enumerate() {
+        if i != 0 {
+            output.push('|');
+        }
+        write!(&mut output, "{json_type}").expect("failed to write type");
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCRAPED_WEBPAGE: &str = include_str!("../test_data/scraped_webpage.json");
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn drift_report() {
+        let scraped_webpage: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let mut baseline = SchemaBaseline::new();
+        baseline
+            .record(&scraped_webpage)
+            .expect("failed to record baseline");
+        assert!(
+            baseline
+                .paths
+                .contains_key("@@entities.deviation.*.media.types[].c")
+        );
+
+        let report = baseline
+            .report(&scraped_webpage)
+            .expect("failed to make report");
+        assert!(report.is_empty(), "{report}");
+
+        let mut value = serde_json::to_value(&scraped_webpage).expect("failed to serialize");
+        for deviation in value["@@entities"]["deviation"]
+            .as_object_mut()
+            .expect("missing deviations")
+            .values_mut()
+        {
+            let deviation = deviation.as_object_mut().expect("invalid deviation");
+            deviation.remove("title");
+            deviation.insert("isNft".into(), serde_json::json!({ "chain": "none" }));
+            deviation.insert("isDownloadable".into(), "yes".into());
+        }
+        let report = baseline.report_value(&value);
+        assert_eq!(report.new_keys, ["@@entities.deviation.*.isNft"]);
+        assert_eq!(report.missing_keys, ["@@entities.deviation.*.title"]);
+        assert!(report.type_mismatches.len() == 1);
+        assert!(report.type_mismatches[0].path == "@@entities.deviation.*.isDownloadable");
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn drift_report_typed_optional_key() {
+        let scraped_webpage: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let mut baseline = SchemaBaseline::new();
+        baseline
+            .record(&scraped_webpage)
+            .expect("failed to record baseline");
+
+        // Drop a key that is parsed into a typed optional field.
+        let mut value: serde_json::Value =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse json");
+        for deviation in value["@@entities"]["deviation"]
+            .as_object_mut()
+            .expect("missing deviations")
+            .values_mut()
+        {
+            deviation
+                .as_object_mut()
+                .expect("invalid deviation")
+                .remove("publishedTime");
+        }
+        let scraped_webpage: ScrapedWebPageInfo =
+            serde_json::from_value(value.clone()).expect("failed to parse scraped webpage info");
+        let report = baseline
+            .report(&scraped_webpage)
+            .expect("failed to make report");
+        assert_eq!(
+            report.missing_keys,
+            ["@@entities.deviation.*.publishedTime"]
+        );
+    }
+
+    #[test]
+    fn drift_report_value() {
+        let mut baseline = SchemaBaseline::new();
+        baseline
+            .record_value(&serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse json"));
+
+        let mut value: serde_json::Value =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse json");
+        for deviation in value["@@entities"]["deviation"]
+            .as_object_mut()
+            .expect("missing deviations")
+            .values_mut()
+        {
+            let deviation = deviation.as_object_mut().expect("invalid deviation");
+            deviation.remove("publishedTime");
+            deviation.insert("isNft".into(), serde_json::json!({ "chain": "none" }));
+        }
+        let report = baseline.report_value(&value);
+        assert_eq!(report.new_keys, ["@@entities.deviation.*.isNft"]);
+        assert_eq!(
+            report.missing_keys,
+            ["@@entities.deviation.*.publishedTime"]
+        );
+        assert!(report.type_mismatches.is_empty());
+    }
+}
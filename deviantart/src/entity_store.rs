@@ -0,0 +1,355 @@
+use crate::Deviation;
+use crate::DeviationAuthor;
+use crate::DeviationExtended;
+use crate::DeviationId;
+use crate::FolderId;
+use crate::ListFolderContentsResponse;
+use crate::Nullable;
+use crate::ScrapedWebPageInfo;
+use crate::UserId;
+use crate::types::scraped_webpage_info::GalleryFolder;
+use crate::types::scraped_webpage_info::Tag;
+use crate::types::scraped_webpage_info::User;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// The prefix of the stream ids of gallery folders.
+const FOLDER_STREAM_PREFIX: &str = "folder-deviations-gallery-";
+
+/// A deduplicated store of entities, merged from scraped pages and api responses.
+///
+/// Entities are keyed by their numeric ids.
+/// Merging an entity that is already in the store replaces it with the newer one.
+#[derive(Debug, Default)]
+pub struct EntityStore {
+    /// Deviations
+    pub deviations: HashMap<DeviationId, Deviation>,
+
+    /// Extended deviation info, by deviation id
+    pub deviations_extended: HashMap<DeviationId, DeviationExtended>,
+
+    /// Gallery folders
+    pub gallery_folders: HashMap<FolderId, GalleryFolder>,
+
+    /// Users
+    pub users: HashMap<UserId, User>,
+
+    /// The deviations in each folder
+    folder_deviations: HashMap<FolderId, FolderDeviations>,
+
+    /// The ids of the folders that each deviation is in, in the order they were added
+    deviation_folders: HashMap<DeviationId, Vec<FolderId>>,
+}
+
+/// The deviations in a folder.
+#[derive(Debug, Default)]
+struct FolderDeviations {
+    /// The deviation ids, in order
+    ids: Vec<DeviationId>,
+
+    /// The deviation ids, for fast lookups
+    id_set: HashSet<DeviationId>,
+}
+
+impl EntityStore {
+    /// Make a new empty [`EntityStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merge the entities and folder streams of a scraped page.
+    pub fn insert_scraped_webpage(&mut self, scraped_webpage: ScrapedWebPageInfo) {
+        if let Some(streams) = scraped_webpage.streams.as_ref() {
+            for (stream_id, stream) in streams.streams.iter() {
+                let Some(folder_id): Option<FolderId> = stream_id
+                    .strip_prefix(FOLDER_STREAM_PREFIX)
+                    .and_then(|folder_id| folder_id.parse().ok())
+                else {
+                    continue;
+                };
+                let Some(stream) = stream.as_with_offset_stream() else {
+                    continue;
+                };
+                self.insert_folder_deviation_ids(folder_id, stream.items.iter().copied());
+            }
+        }
+
+        let Nullable::Value(entities) = scraped_webpage.entities else {
+            return;
+        };
+        for user in entities
+            .user
+            .into_iter()
+            .flat_map(|users| users.entries.into_values())
+        {
+            self.insert_user(user);
+        }
+        for deviation in entities.deviation.entries.into_values() {
+            self.insert_deviation(deviation);
+        }
+        for deviation_extended in entities
+            .deviation_extended
+            .into_iter()
+            .flat_map(|deviations_extended| deviations_extended.entries.into_values())
+        {
+            self.insert_deviation_extended(deviation_extended);
+        }
+        for gallery_folder in entities
+            .gallery_folder
+            .into_iter()
+            .flat_map(|gallery_folders| gallery_folders.entries.into_values())
+        {
+            self.insert_gallery_folder(gallery_folder);
+        }
+    }
+
+    /// Merge a page of folder contents for the given folder id.
+    pub fn insert_list_folder_contents(
+        &mut self,
+        folder_id: FolderId,
+        list_folder_contents: ListFolderContentsResponse,
+    ) {
+        self.insert_folder_deviation_ids(
+            folder_id,
+            list_folder_contents
+                .results
+                .iter()
+                .map(|deviation| deviation.deviation_id),
+        );
+        for deviation in list_folder_contents.results {
+            self.insert_deviation(deviation);
+        }
+    }
+
+    /// Merge a deviation.
+    ///
+    /// If the deviation embeds its author, the author is merged too.
+    pub fn insert_deviation(&mut self, deviation: Deviation) {
+        if let Some(DeviationAuthor::Embedded(author)) = deviation.author.as_ref() {
+            self.insert_user(author.clone());
+        }
+        self.deviations.insert(deviation.deviation_id, deviation);
+    }
+
+    /// Merge extended deviation info.
+    pub fn insert_deviation_extended(&mut self, deviation_extended: DeviationExtended) {
+        self.deviations_extended.insert(
+            deviation_extended.parent_deviation_entity_id,
+            deviation_extended,
+        );
+    }
+
+    /// Merge a gallery folder.
+    pub fn insert_gallery_folder(&mut self, gallery_folder: GalleryFolder) {
+        self.gallery_folders
+            .insert(gallery_folder.folder_id, gallery_folder);
+    }
+
+    /// Merge a user.
+    pub fn insert_user(&mut self, user: User) {
+        self.users.insert(user.user_id, user);
+    }
+
+    /// Add deviation ids to a folder, skipping ones that are already in it.
+    fn insert_folder_deviation_ids(
+        &mut self,
+        folder_id: FolderId,
+        deviation_ids: impl Iterator<Item = DeviationId>,
+    ) {
+        let folder_deviations = self.folder_deviations.entry(folder_id).or_default();
+        for deviation_id in deviation_ids {
+            if folder_deviations.id_set.insert(deviation_id) {
+                folder_deviations.ids.push(deviation_id);
+                self.deviation_folders
+                    .entry(deviation_id)
+                    .or_default()
+                    .push(folder_id);
+            }
+        }
+    }
+
+    /// Get the ids of the deviations in a folder, in order.
+    pub fn folder_deviation_ids(&self, folder_id: FolderId) -> Option<&[DeviationId]> {
+        self.folder_deviations
+            .get(&folder_id)
+            .map(|folder_deviations| folder_deviations.ids.as_slice())
+    }
+
+    /// Get a deviation by id, linked to the rest of the store.
+    pub fn get_deviation(&self, deviation_id: DeviationId) -> Option<DeviationNode<'_>> {
+        Some(DeviationNode {
+            deviation: self.deviations.get(&deviation_id)?,
+            store: self,
+        })
+    }
+
+    /// Iterate over all deviations, linked to the rest of the store.
+    pub fn iter_deviations(&self) -> impl Iterator<Item = DeviationNode<'_>> {
+        self.deviations.values().map(|deviation| DeviationNode {
+            deviation,
+            store: self,
+        })
+    }
+
+    /// Get a gallery folder by id, linked to the rest of the store.
+    pub fn get_folder(&self, folder_id: FolderId) -> Option<FolderNode<'_>> {
+        Some(FolderNode {
+            folder: self.gallery_folders.get(&folder_id)?,
+            store: self,
+        })
+    }
+}
+
+/// A deviation in an [`EntityStore`].
+#[derive(Debug, Clone, Copy)]
+pub struct DeviationNode<'a> {
+    /// The deviation
+    pub deviation: &'a Deviation,
+
+    /// The store
+    store: &'a EntityStore,
+}
+
+impl<'a> DeviationNode<'a> {
+    /// Get the user id of the author.
+    pub fn author_id(&self) -> Option<UserId> {
+        self.deviation.author.as_ref()?.user_id()
+    }
+
+    /// Get the author.
+    pub fn author(&self) -> Option<&'a User> {
+        self.store.users.get(&self.author_id()?)
+    }
+
+    /// Get the extended info.
+    pub fn extended(&self) -> Option<&'a DeviationExtended> {
+        self.store
+            .deviations_extended
+            .get(&self.deviation.deviation_id)
+    }
+
+    /// Get the ids of the folders that this deviation is known to be in.
+    pub fn folder_ids(&self) -> impl Iterator<Item = FolderId> + 'a {
+        self.store
+            .deviation_folders
+            .get(&self.deviation.deviation_id)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// Get the folders that this deviation is known to be in.
+    ///
+    /// Folders without a gallery folder entity are skipped. Use [`DeviationNode::folder_ids`] to get those.
+    pub fn folders(&self) -> impl Iterator<Item = FolderNode<'a>> + 'a {
+        let store = self.store;
+        self.folder_ids()
+            .filter_map(move |folder_id| store.get_folder(folder_id))
+    }
+
+    /// Get the tags.
+    ///
+    /// This is empty if the extended info is not in the store.
+    pub fn tags(&self) -> impl Iterator<Item = &'a Tag> + 'a {
+        self.extended()
+            .and_then(|extended| extended.tags.typed())
+            .into_iter()
+            .flatten()
+    }
+}
+
+/// A gallery folder in an [`EntityStore`].
+#[derive(Debug, Clone, Copy)]
+pub struct FolderNode<'a> {
+    /// The folder
+    pub folder: &'a GalleryFolder,
+
+    /// The store
+    store: &'a EntityStore,
+}
+
+impl<'a> FolderNode<'a> {
+    /// Get the owner.
+    pub fn owner(&self) -> Option<&'a User> {
+        self.store.users.get(&self.folder.owner)
+    }
+
+    /// Get the deviations in this folder that are in the store, in order.
+    pub fn deviations(&self) -> impl Iterator<Item = DeviationNode<'a>> + 'a {
+        let store = self.store;
+        store
+            .folder_deviation_ids(self.folder.folder_id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |deviation_id| store.get_deviation(*deviation_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCRAPED_WEBPAGE: &str = include_str!("../test_data/scraped_webpage.json");
+
+    #[test]
+    fn entity_store() {
+        let scraped_webpage: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let mut store = EntityStore::new();
+        store.insert_scraped_webpage(scraped_webpage);
+
+        let deviation = store
+            .get_deviation(DeviationId(17941730))
+            .expect("missing deviation");
+        let author = deviation.author().expect("missing author");
+        assert!(author.username == "lexidh");
+
+        let current = store
+            .get_deviation(DeviationId(119577071))
+            .expect("missing deviation");
+        assert!(current.extended().is_some());
+
+        // Merge a folder listing with a deviation that is already in the store.
+        let mut deviation: serde_json::Value = serde_json::from_str(SCRAPED_WEBPAGE)
+            .map(|mut value: serde_json::Value| value["@@entities"]["deviation"]["17941730"].take())
+            .expect("failed to parse scraped webpage info");
+        deviation["author"] = serde_json::json!({
+            "userId": 375780,
+            "username": "lexidh-renamed",
+        });
+        let list_folder_contents: ListFolderContentsResponse =
+            serde_json::from_value(serde_json::json!({
+                "hasMore": false,
+                "nextOffset": null,
+                "results": [deviation.clone(), deviation],
+            }))
+            .expect("failed to parse list folder contents");
+        let deviation_count = store.deviations.len();
+        store.insert_list_folder_contents(FolderId::Id(42), list_folder_contents);
+        store.insert_gallery_folder(
+            serde_json::from_value(serde_json::json!({
+                "folderId": 42,
+                "name": "Featured",
+                "owner": 375780,
+            }))
+            .expect("failed to parse gallery folder"),
+        );
+
+        assert!(store.deviations.len() == deviation_count);
+        assert_eq!(
+            store.folder_deviation_ids(FolderId::Id(42)),
+            Some([DeviationId(17941730)].as_slice())
+        );
+
+        let deviation = store
+            .get_deviation(DeviationId(17941730))
+            .expect("missing deviation");
+        let author = deviation.author().expect("missing author");
+        assert!(author.username == "lexidh-renamed");
+        let folders: Vec<_> = deviation.folders().collect();
+        assert!(folders.len() == 1);
+        assert!(folders[0].folder.name == "Featured");
+        assert!(folders[0].owner().is_some());
+        assert!(folders[0].deviations().count() == 1);
+    }
+}
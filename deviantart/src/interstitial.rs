@@ -0,0 +1,133 @@
+/// Titles of bot challenge and captcha pages.
+const CHALLENGE_TITLES: &[&str] = &["Just a moment...", "Attention Required! | Cloudflare"];
+
+/// Markup that only appears on bot challenge and captcha pages.
+///
+/// Normal pages also load Cloudflare scripts, so script urls are not enough.
+/// These include raw quotes, which cannot come from the escaped initial state of a normal page.
+const CHALLENGE_MARKUP: &[&str] = &[
+    "id=\"challenge-form\"",
+    "id=\"challenge-running\"",
+    "class=\"cf-browser-verification\"",
+    "src=\"https://geo.captcha-delivery.com/captcha/",
+];
+
+/// Titles of maintenance pages.
+const MAINTENANCE_TITLES: &[&str] = &[
+    "DeviantArt: Maintenance",
+    "DeviantArt: Down for Maintenance",
+];
+
+/// Titles of deleted deviation pages.
+const DELETED_TITLES: &[&str] = &["DeviantArt: Deviation Deleted"];
+
+/// Titles of not found pages.
+const NOT_FOUND_TITLES: &[&str] = &["DeviantArt: 404", "404 Not Found"];
+
+/// Titles of pages that hide mature content.
+const MATURE_CONTENT_GATE_TITLES: &[&str] = &["DeviantArt: Mature Content"];
+
+/// A page that DeviantArt serves instead of the requested page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interstitial {
+    /// A bot challenge or captcha page.
+    Challenge,
+
+    /// A page saying that the site is down for maintenance.
+    Maintenance,
+
+    /// A page saying that a deviation was deleted.
+    Deleted,
+
+    /// A page saying that the requested page does not exist.
+    NotFound,
+
+    /// A page saying that the content is only visible to logged in adults.
+    MatureContentGate,
+}
+
+impl Interstitial {
+    /// Try to detect an interstitial page from its html.
+    ///
+    /// Only the page title and markup specific to interstitial pages are checked,
+    /// as titles, descriptions and comments of normal pages may contain any text.
+    pub(crate) fn detect(html: &str) -> Option<Self> {
+        let title = get_title(html).unwrap_or("");
+        let has_title = |titles: &[&str]| titles.iter().any(|t| t.eq_ignore_ascii_case(title));
+
+        if has_title(CHALLENGE_TITLES) || CHALLENGE_MARKUP.iter().any(|m| html.contains(m)) {
+            Some(Self::Challenge)
+        } else if has_title(MAINTENANCE_TITLES) {
+            Some(Self::Maintenance)
+        } else if has_title(DELETED_TITLES) {
+            Some(Self::Deleted)
+        } else if has_title(NOT_FOUND_TITLES) {
+            Some(Self::NotFound)
+        } else if has_title(MATURE_CONTENT_GATE_TITLES) {
+            Some(Self::MatureContentGate)
+        } else {
+            None
+        }
+    }
+}
+
+/// Get the text of the first `<title>` element of a html page.
+fn get_title(html: &str) -> Option<&str> {
+    let start = html.find("<title")?;
+    let rest = &html[start..];
+    let rest = &rest[rest.find('>')? + 1..];
+    let end = rest.find("</title>")?;
+
+    Some(rest[..end].trim())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let page = "<html><head><title>Just a moment...</title></head><body><script src=\"/cdn-cgi/challenge-platform/h/b/orchestrate/chl_page/v1\"></script></body></html>";
+        assert_eq!(Interstitial::detect(page), Some(Interstitial::Challenge));
+
+        let page = "<html><head><title>DeviantArt</title></head><body><form id=\"challenge-form\"></form></body></html>";
+        assert_eq!(Interstitial::detect(page), Some(Interstitial::Challenge));
+
+        let page = "<html><head><title>DeviantArt: Maintenance</title></head></html>";
+        assert_eq!(Interstitial::detect(page), Some(Interstitial::Maintenance));
+
+        let page = "<html><head><title>DeviantArt: Deviation Deleted</title></head></html>";
+        assert_eq!(Interstitial::detect(page), Some(Interstitial::Deleted));
+
+        let page = "<html><head><title>DeviantArt: 404</title></head></html>";
+        assert_eq!(Interstitial::detect(page), Some(Interstitial::NotFound));
+
+        let page = "<html><head><title>DeviantArt: Mature Content</title></head></html>";
+        assert_eq!(
+            Interstitial::detect(page),
+            Some(Interstitial::MatureContentGate)
+        );
+
+        assert_eq!(Interstitial::detect("<html></html>"), None);
+    }
+
+    #[test]
+    fn normal_pages_are_not_interstitials() {
+        // Normal pages load Cloudflare scripts,
+        // and their titles, descriptions and comments may contain any text.
+        let page = concat!(
+            "<html><head>",
+            "<title>This deviation was deleted by someone on DeviantArt</title>",
+            "<script src=\"/cdn-cgi/challenge-platform/scripts/jsd/main.js\"></script>",
+            "</head><body>",
+            "<p>This content is intended for mature audiences. Just a moment...</p>",
+            "<p>This deviation was deleted, DeviantArt: 404, down for maintenance</p>",
+            "</body></html>",
+        );
+        assert_eq!(Interstitial::detect(page), None);
+
+        // Quotes in the initial state are escaped, so they cannot fake challenge markup.
+        let page = r#"<script>window.__INITIAL_STATE__ = JSON.parse("{\"html\":\"<form id=\\\"challenge-form\\\">\"}");</script>"#;
+        assert_eq!(Interstitial::detect(page), None);
+    }
+}
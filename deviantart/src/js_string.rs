@@ -0,0 +1,325 @@
+/// An invalid escape sequence in a JS string literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid escape sequence at byte {position}")]
+pub struct InvalidEscapeError {
+    /// The byte position of the backslash that starts the escape sequence
+    pub position: usize,
+}
+
+/// Unescape a `"` quoted JS string literal at the start of `input`, after the opening quote.
+///
+/// This handles every escape sequence that a non-strict mode JS parser accepts.
+/// Lone surrogates cannot be stored in a Rust string, so they are replaced with U+FFFD.
+/// This returns the unescaped contents, which borrow from `input` if there are no escapes,
+/// or are written to `buffer` otherwise.
+/// This returns `None` if there is no closing quote.
+pub(crate) fn unescape_quoted<'a>(
+    input: &'a str,
+    buffer: &'a mut String,
+) -> Result<Option<&'a str>, InvalidEscapeError> {
+    // Fast path for literals without escapes.
+    match input.find(['\\', '"']) {
+        None => return Ok(None),
+        Some(end) if input.as_bytes()[end] == b'"' => return Ok(Some(&input[..end])),
+        Some(_) => {}
+    }
+
+    buffer.clear();
+    match unescape_into(input, Some('"'), buffer)? {
+        Some(_) => Ok(Some(buffer.as_str())),
+        None => Ok(None),
+    }
+}
+
+/// Unescape `input` into `output` in one pass, stopping at an unescaped `quote` if there is one.
+///
+/// This returns the position of the closing quote, if it was found.
+fn unescape_into(
+    input: &str,
+    quote: Option<char>,
+    output: &mut String,
+) -> Result<Option<usize>, InvalidEscapeError> {
+    // A high surrogate that may be followed by a low surrogate escape.
+    let mut high_surrogate: Option<u16> = None;
+    let mut position = 0;
+    loop {
+        let rest = &input[position..];
+        let next = match quote {
+            Some(quote) => rest.find(['\\', quote]),
+            None => rest.find('\\'),
+        };
+        let Some(offset) = next else {
+            flush_surrogate(output, &mut high_surrogate);
+            output.push_str(rest);
+            return Ok(None);
+        };
+
+        // Copy the run of plain text before the escape or quote in one go.
+        if offset > 0 {
+            flush_surrogate(output, &mut high_surrogate);
+            output.push_str(&rest[..offset]);
+        }
+        position += offset;
+
+        if input.as_bytes()[position] != b'\\' {
+            flush_surrogate(output, &mut high_surrogate);
+            return Ok(Some(position));
+        }
+
+        let (len, value) =
+            parse_escape(&input[position + 1..]).ok_or(InvalidEscapeError { position })?;
+        match value {
+            Some(value) => push_code_point(output, &mut high_surrogate, value),
+            None => flush_surrogate(output, &mut high_surrogate),
+        }
+        position += 1 + len;
+    }
+}
+
+/// Parse the escape sequence after a backslash.
+///
+/// This returns the length of the sequence in bytes,
+/// and the code point or UTF-16 code unit that it produces, which is `None` for line continuations.
+fn parse_escape(input: &str) -> Option<(usize, Option<u32>)> {
+    let mut chars = input.char_indices().peekable();
+    let (_, escape) = chars.next()?;
+    let value = match escape {
+        'u' if chars.next_if(|(_, c)| *c == '{').is_some() => {
+            let mut code_point: u32 = 0;
+            let mut len = 0;
+            loop {
+                let (_, c) = chars.next()?;
+                if c == '}' {
+                    break;
+                }
+                code_point = code_point * 16 + c.to_digit(16)?;
+                len += 1;
+                if code_point > 0x10FFFF {
+                    return None;
+                }
+            }
+            if len == 0 {
+                return None;
+            }
+            Some(code_point)
+        }
+        'u' => Some(parse_hex(&mut chars, 4)?),
+        'x' => Some(parse_hex(&mut chars, 2)?),
+        '0'..='7' => {
+            // `\0` not followed by a digit is a null character,
+            // everything else is a legacy octal escape of up to 3 digits.
+            let mut code_point = escape.to_digit(8)?;
+            let max_len = if escape <= '3' { 3 } else { 2 };
+            for _ in 1..max_len {
+                match chars.next_if(|(_, c)| matches!(c, '0'..='7')) {
+                    Some((_, c)) => code_point = code_point * 8 + c.to_digit(8)?,
+                    None => break,
+                }
+            }
+            Some(code_point)
+        }
+        'n' => Some('\n'.into()),
+        't' => Some('\t'.into()),
+        'r' => Some('\r'.into()),
+        'b' => Some('\u{8}'.into()),
+        'f' => Some('\u{c}'.into()),
+        'v' => Some('\u{b}'.into()),
+        // Line continuations produce nothing.
+        '\r' => {
+            chars.next_if(|(_, c)| *c == '\n');
+            None
+        }
+        '\n' | '\u{2028}' | '\u{2029}' => None,
+        // Everything else, like `\"`, `\'`, `\\` and `\/`, escapes to itself.
+        c => Some(c.into()),
+    };
+    let len = chars.peek().map_or(input.len(), |(i, _)| *i);
+
+    Some((len, value))
+}
+
+/// Parse exactly `len` hex digits.
+fn parse_hex<I>(chars: &mut std::iter::Peekable<I>, len: usize) -> Option<u32>
+where
+    I: Iterator<Item = (usize, char)>,
+{
+    let mut value = 0;
+    for _ in 0..len {
+        let (_, c) = chars.next()?;
+        value = value * 16 + c.to_digit(16)?;
+    }
+    Some(value)
+}
+
+/// Push a code point or UTF-16 code unit, pairing surrogates.
+fn push_code_point(output: &mut String, high_surrogate: &mut Option<u16>, value: u32) {
+    match value {
+        0xD800..=0xDBFF => {
+            flush_surrogate(output, high_surrogate);
+            *high_surrogate = Some(value as u16);
+        }
+        0xDC00..=0xDFFF => match high_surrogate.take() {
+            Some(high) => {
+                let code_point = 0x10000 + ((u32::from(high) - 0xD800) << 10) + (value - 0xDC00);
+                output.push(char::from_u32(code_point).expect("invalid surrogate pair"));
+            }
+            None => output.push(char::REPLACEMENT_CHARACTER),
+        },
+        _ => {
+            flush_surrogate(output, high_surrogate);
+            output.push(char::from_u32(value).expect("invalid code point"));
+        }
+    }
+}
+
+/// Replace a high surrogate that was not followed by a low surrogate.
+fn flush_surrogate(output: &mut String, high_surrogate: &mut Option<u16>) {
+    if high_surrogate.take().is_some() {
+        output.push(char::REPLACEMENT_CHARACTER);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Unescape a whole string.
+    fn unescape(input: &str) -> Result<String, InvalidEscapeError> {
+        let mut output = String::new();
+        unescape_into(input, None, &mut output)?;
+        Ok(output)
+    }
+
+    /// Tricky strings that should round-trip through a JS literal and JSON.
+    const CORPUS: &[&str] = &[
+        "",
+        "plain ascii title",
+        "\"Quoted\" and 'single quoted'",
+        "back\\slash and \\\"escaped quote\\\"",
+        "C:\\Users\\new folder\\temp",
+        "slash/es and </script> tags",
+        "line one\nline two\r\nline three\ttabbed",
+        "control \u{0} \u{8} \u{b} \u{c} \u{1f} chars",
+        "emoji 🐉🔥 and flags 🇯🇵 and zwj 👩‍🎨",
+        "日本語のタイトル「龍」",
+        "한국어 제목과 中文标题",
+        "Ünïcödé — “curly quotes” … and ‘apostrophes’",
+        "line separators \u{2028} and \u{2029}",
+        "It was the best of times, it was the worst of times,\n\
+         it was the age of wisdom, it was the age of foolishness…\n\
+         \t— Charles Dickens, \"A Tale of Two Cities\"",
+        "\\u0041 is not an escape here, nor is \\n",
+        "trailing backslash \\",
+    ];
+
+    /// Escape a string as a JS literal, like DeviantArt does.
+    fn escape_simple(input: &str) -> String {
+        let mut output = String::new();
+        for c in input.chars() {
+            match c {
+                '\\' => output.push_str("\\\\"),
+                '"' => output.push_str("\\\""),
+                '\'' => output.push_str("\\'"),
+                '\n' => output.push_str("\\n"),
+                '/' => output.push_str("\\/"),
+                c => output.push(c),
+            }
+        }
+        output
+    }
+
+    /// Escape every non-alphanumeric character in a JS literal, using all escape forms.
+    fn escape_everything(input: &str) -> String {
+        let mut output = String::new();
+        for (i, c) in input.chars().enumerate() {
+            if c.is_ascii_alphanumeric() {
+                output.push(c);
+                continue;
+            }
+
+            match (u32::from(c), i % 3) {
+                (value @ ..=0xFF, 0) => output.push_str(&format!("\\x{value:02x}")),
+                (value @ ..=0xFFFF, 1) => output.push_str(&format!("\\u{value:04X}")),
+                (value, 2) => output.push_str(&format!("\\u{{{value:x}}}")),
+                _ => {
+                    let mut buffer = [0; 2];
+                    for unit in c.encode_utf16(&mut buffer) {
+                        output.push_str(&format!("\\u{unit:04x}"));
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn escapes() {
+        let cases = [
+            (r#"\"\'\\\/"#, "\"'\\/"),
+            (r"\n\t\r\b\f\v\0", "\n\t\r\u{8}\u{c}\u{b}\u{0}"),
+            (r"\x41\x7e\xe9", "A~é"),
+            (r"\u0041\u00e9\u65e5", "Aé日"),
+            (r"\u{41}\u{1F409}\u{0000065E5}", "A🐉日"),
+            (r"\uD83D\uDC09", "🐉"),
+            (r"\ud83d\udc09\ud83d\udd25", "🐉🔥"),
+            (r"\uD83D", "\u{FFFD}"),
+            (r"\uDC09x", "\u{FFFD}x"),
+            (r"\uD83Dx\uDC09", "\u{FFFD}x\u{FFFD}"),
+            (r"\101\60\1010\400", "A0A0\u{20}0"),
+            (r"\8\9\q\ü", "89qü"),
+            ("a\\\nb\\\r\nc\\\rd\\\u{2028}e", "abcde"),
+            (r"\\u0041\\\u0041", "\\u0041\\A"),
+        ];
+        for (input, expected) in cases {
+            let output = unescape(input).expect("failed to unescape");
+            assert_eq!(output, expected, "{input}");
+        }
+
+        let invalid = [
+            ("\\", 0),
+            (r"ab\x4", 2),
+            (r"\xZZ", 0),
+            (r"\u004", 0),
+            (r"\u{}", 0),
+            (r"\u{110000}", 0),
+            (r"\u{41", 0),
+        ];
+        for (input, position) in invalid {
+            let error = unescape(input).expect_err("unescaped an invalid escape");
+            assert_eq!(error, InvalidEscapeError { position }, "{input}");
+        }
+    }
+
+    #[test]
+    fn quoted() {
+        let mut buffer = String::new();
+        let cases = [
+            (r#"plain"); rest"#, Some("plain")),
+            (r#"a \"quote\" and \\"); rest"#, Some(r#"a "quote" and \"#)),
+            (r#"\u65e5\u672c"#, None),
+            ("unterminated", None),
+        ];
+        for (input, expected) in cases {
+            let output = unescape_quoted(input, &mut buffer).expect("failed to unescape");
+            assert_eq!(output, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for text in CORPUS {
+            let json = serde_json::to_string(&serde_json::json!({ "title": text }))
+                .expect("failed to serialize");
+
+            for literal in [escape_simple(&json), escape_everything(&json)] {
+                let unescaped = unescape(&literal).expect("failed to unescape");
+                assert_eq!(unescaped, json);
+
+                let value: serde_json::Value =
+                    serde_json::from_str(&unescaped).expect("failed to parse");
+                let title = value["title"].as_str().expect("missing title");
+                assert_eq!(title.as_bytes(), text.as_bytes());
+            }
+        }
+    }
+}
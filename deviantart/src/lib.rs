@@ -1,17 +1,78 @@
 /// The client
 mod client;
+/// Schema drift reporting
+mod drift;
+/// The [`EntityStore`]
+mod entity_store;
+/// Detection of challenge and error pages
+mod interstitial;
+/// Unescaping of JS string literals
+mod js_string;
+/// The [`ClientPool`]
+mod pool;
+/// The [`DeviationSource`] trait
+pub mod source;
 /// API types
 pub mod types;
 
 pub use self::client::Client;
+pub use self::client::ClientBuilder;
+pub use self::client::ClientEvent;
+pub use self::client::CredentialProvider;
+pub use self::client::Credentials;
+#[cfg(feature = "encrypted-session-store")]
+pub use self::client::EncryptedSessionStore;
+pub use self::client::Endpoints;
+pub use self::client::EventHandler;
+pub use self::client::FileSessionStore;
+pub use self::client::FixtureMode;
+pub use self::client::Fixtures;
+pub use self::client::LoginFailure;
+pub use self::client::LoginStep;
+pub use self::client::MemorySessionStore;
+pub use self::client::PendingLogin;
+pub use self::client::RateLimiter;
+pub use self::client::RetryPolicy;
+pub use self::client::Session;
+pub use self::client::SessionStore;
+pub use self::client::TlsBackend;
+pub use self::client::VerificationKind;
+pub use self::drift::DriftReport;
+pub use self::drift::JsonType;
+pub use self::drift::SchemaBaseline;
+pub use self::drift::TypeMismatch;
+pub use self::entity_store::DeviationNode;
+pub use self::entity_store::EntityStore;
+pub use self::entity_store::FolderNode;
+pub use self::pool::ClientPool;
+pub use self::pool::PoolStrategy;
+pub use self::source::DeviationSource;
+pub use self::types::BrowseStreamItem;
+pub use self::types::ContinuableStream;
 pub use self::types::Deviation;
+pub use self::types::DeviationAuthor;
 pub use self::types::DeviationExtended;
+pub use self::types::DeviationId;
+pub use self::types::DeviationKind;
+pub use self::types::EntityKind;
+pub use self::types::FolderId;
 pub use self::types::GetFullviewUrlError;
 pub use self::types::GetFullviewUrlOptions;
+pub use self::types::IdMap;
+pub use self::types::LazyScrapedWebPageInfo;
+pub use self::types::Lenient;
 pub use self::types::ListFolderContentsResponse;
+pub use self::types::Nullable;
 pub use self::types::OEmbed;
 pub use self::types::ScrapedStashInfo;
 pub use self::types::ScrapedWebPageInfo;
+pub use self::types::StashId;
+pub use self::types::Stream;
+pub use self::types::StreamPageResponse;
+pub use self::types::StreamPosition;
+pub use self::types::Timestamp;
+pub use self::types::UnknownKind;
+pub use self::types::UserId;
 pub use url::Url;
 
 /// Library Error
@@ -21,6 +82,83 @@ pub enum Error {
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
 
+    /// The server responded with an error status
+    #[error("\"{url}\" returned {status}")]
+    HttpStatus {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// The requested page does not exist
+    #[error("\"{url}\" was not found ({status})")]
+    NotFound {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// The requested deviation was deleted
+    #[error("\"{url}\" was deleted ({status})")]
+    Deleted {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// The requested page requires logging in
+    #[error("\"{url}\" requires logging in ({status})")]
+    LoginRequired {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// The requested page is hidden behind the mature content filter
+    #[error("\"{url}\" is mature content ({status})")]
+    MatureContentGated {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// The server kept rate limiting a request after all retries
+    #[error("\"{url}\" was rate limited ({status})")]
+    RateLimited {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+
+        /// How long the server asked us to wait, if it said.
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// The server served a bot challenge or captcha instead of the requested page
+    #[error("\"{url}\" was blocked by a challenge page ({status})")]
+    BlockedByChallenge {
+        /// The request url
+        url: Url,
+
+        /// The response status
+        status: reqwest::StatusCode,
+    },
+
+    /// Invalid header value
+    #[error(transparent)]
+    InvalidHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
+
     /// Invalid Url
     #[error(transparent)]
     Url(#[from] url::ParseError),
@@ -33,6 +171,10 @@ pub enum Error {
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
+    /// An io error occured
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     /// A scraped web page was invalid
     #[error("invalid scraped webpage")]
     InvalidScrapedWebPage(#[from] self::types::scraped_webpage_info::FromHtmlStrError),
@@ -45,6 +187,10 @@ pub enum Error {
     #[error("sign in failed")]
     SignInFailed,
 
+    /// Logging in failed for a known reason
+    #[error("login failed: {0}")]
+    LoginFailed(#[from] LoginFailure),
+
     /// Missing a field
     #[error("missing field \"{name}\"")]
     MissingField {
@@ -62,7 +208,60 @@ pub enum Error {
 
     /// Missing the Deviation of the given id
     #[error("missing deviation {0}")]
-    MissingDeviation(u64),
+    MissingDeviation(DeviationId),
+
+    /// The page does not have a current deviation
+    #[error("missing current deviation")]
+    MissingCurrentDeviation,
+
+    /// Missing the user with the given name or id
+    #[error("missing user \"{0}\"")]
+    MissingUser(String),
+
+    /// There is no recorded response for a request in replay mode
+    #[error("missing fixture for {method} \"{url}\"")]
+    MissingFixture {
+        /// The request method
+        method: String,
+
+        /// The request url
+        url: Url,
+    },
+
+    /// A recorded response has an invalid status
+    #[error("invalid fixture status {0}")]
+    InvalidFixtureStatus(u16),
+
+    /// A session could not be encrypted or decrypted.
+    ///
+    /// This usually means that the passphrase is wrong or the saved session is corrupt.
+    #[error("failed to encrypt or decrypt session")]
+    SessionEncryption,
+
+    /// A line of a cookies.txt file is invalid
+    #[error("invalid cookies.txt line {line}")]
+    InvalidCookiesTxt {
+        /// The line number, starting from 1
+        line: usize,
+    },
+
+    /// A stream cannot be continued, because it has no request endpoint
+    #[error("stream cannot be continued")]
+    StreamNotContinuable {
+        /// The stream id
+        stream_id: Option<String>,
+
+        /// The name of the page action that fetches more items, if there is one
+        fetch_next_callback: Option<String>,
+    },
+
+    /// A [`ClientBuilder`] was given options that cannot be used together
+    #[error("invalid client builder options: {0}")]
+    InvalidBuilder(&'static str),
+
+    /// Every client in a [`ClientPool`] was taken out of rotation
+    #[error("no clients are available in the pool")]
+    NoAvailableClients,
 
     /// A cookie store error occured
     #[error("cookie store error")]
@@ -0,0 +1,325 @@
+use crate::Client;
+use crate::ClientEvent;
+use crate::DeviationSource;
+use crate::Error;
+use crate::EventHandler;
+use crate::ScrapedWebPageInfo;
+use crate::source::DeviationInfo;
+use crate::source::FolderInfo;
+use crate::source::SearchPage;
+use crate::types::scraped_webpage_info::FromHtmlStrError;
+use crate::types::scraped_webpage_info::User;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+/// How a [`ClientPool`] picks a client for each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PoolStrategy {
+    /// Use each client in turn.
+    #[default]
+    RoundRobin,
+
+    /// Use the client that was rate limited the longest time ago.
+    ///
+    /// Clients that were never rate limited are used first, in turn.
+    LeastRecentlyRateLimited,
+}
+
+/// The state of a client in a pool.
+#[derive(Debug, Default)]
+struct EntryState {
+    /// When the client was last rate limited
+    last_rate_limited: Option<Instant>,
+
+    /// Whether the client was taken out of rotation
+    retired: bool,
+}
+
+/// A client in a pool.
+#[derive(Debug)]
+struct Entry {
+    /// The client
+    client: Client,
+
+    /// The state
+    state: Arc<Mutex<EntryState>>,
+}
+
+/// A pool of clients, usually logged in to different accounts.
+///
+/// Requests are spread across the clients, so one account's rate limit is not the bottleneck.
+/// Each client should have its own cookie store and rate limiter.
+/// A client whose session is challenged, or cannot be logged back in, is taken out of rotation.
+#[derive(Debug, Clone)]
+pub struct ClientPool {
+    /// The clients
+    entries: Arc<[Entry]>,
+
+    /// The strategy for picking clients
+    strategy: PoolStrategy,
+
+    /// The next client to use, for round robin
+    next: Arc<AtomicUsize>,
+}
+
+impl ClientPool {
+    /// Make a new [`ClientPool`] from some clients.
+    ///
+    /// This replaces the event handler of each client with one that tracks rate limits,
+    /// and passes events on to the original handler.
+    pub fn new(clients: impl IntoIterator<Item = Client>, strategy: PoolStrategy) -> Self {
+        let entries = clients
+            .into_iter()
+            .map(|mut client| {
+                let state = Arc::new(Mutex::new(EntryState::default()));
+                let event_handler = client.event_handler.take();
+                client.event_handler = Some({
+                    let state = state.clone();
+                    EventHandler::new(move |event| {
+                        if let ClientEvent::RateLimited { .. } = event {
+                            state
+                                .lock()
+                                .expect("pool entry is poisoned")
+                                .last_rate_limited = Some(Instant::now());
+                        }
+                        if let Some(event_handler) = event_handler.as_ref() {
+                            event_handler.emit(event);
+                        }
+                    })
+                });
+
+                Entry { client, state }
+            })
+            .collect();
+
+        Self {
+            entries,
+            strategy,
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Get the number of clients, including ones that were taken out of rotation.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no clients.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the number of clients in rotation.
+    pub fn active_len(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.state.lock().expect("pool entry is poisoned").retired)
+            .count()
+    }
+
+    /// Pick the next client in rotation, returning its index and the client.
+    pub fn pick(&self) -> Result<(usize, Client), Error> {
+        let counter = self.next.fetch_add(1, Ordering::SeqCst);
+
+        // Lock each entry once, so the choice is made from one view of the pool.
+        let states: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| entry.state.lock().expect("pool entry is poisoned"))
+            .collect();
+        let live: Vec<_> = states
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| !state.retired)
+            .map(|(index, _)| index)
+            .collect();
+        if live.is_empty() {
+            return Err(Error::NoAvailableClients);
+        }
+
+        let start = counter % live.len();
+        let index = match self.strategy {
+            PoolStrategy::RoundRobin => live[start],
+            PoolStrategy::LeastRecentlyRateLimited => {
+                // `None` sorts before any time, so clients that were never rate limited win.
+                // Ties go to the first client in turn.
+                live[start..]
+                    .iter()
+                    .chain(live[..start].iter())
+                    .copied()
+                    .min_by_key(|index| states[*index].last_rate_limited)
+                    .expect("live clients are not empty")
+            }
+        };
+
+        Ok((index, self.entries[index].client.clone()))
+    }
+
+    /// Take the client at the given index out of rotation.
+    pub fn retire(&self, index: usize) {
+        self.entries[index]
+            .state
+            .lock()
+            .expect("pool entry is poisoned")
+            .retired = true;
+    }
+
+    /// Run a request on a client from the pool.
+    ///
+    /// If the request fails because the client's session is logged out or challenged,
+    /// that client is taken out of rotation and the request is run again on another client.
+    pub async fn run<F, Fut, T>(&self, func: F) -> Result<T, Error>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        loop {
+            let (index, client) = self.pick()?;
+            match func(client).await {
+                Err(error) if is_session_error(&error) => {
+                    self.retire(index);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Scrape a webpage for info.
+    ///
+    /// A client with a credential provider that is still logged out after trying to log in again is taken out of rotation.
+    /// Clients without a credential provider may return logged out pages.
+    pub async fn scrape_webpage(&self, url: &str) -> Result<ScrapedWebPageInfo, Error> {
+        loop {
+            let (index, client) = self.pick()?;
+            match client.scrape_webpage(url).await {
+                // The client already tried to log in again, so a logged out page means that failed.
+                Ok(scraped_webpage)
+                    if !scraped_webpage.is_logged_in() && client.credential_provider.is_some() =>
+                {
+                    self.retire(index);
+                }
+                Err(error) if is_session_error(&error) => {
+                    self.retire(index);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns `true` if an error means that a client's session is no longer usable.
+fn is_session_error(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::LoginRequired { .. }
+            | Error::BlockedByChallenge { .. }
+            | Error::LoginFailed(_)
+            | Error::InvalidScrapedWebPage(FromHtmlStrError::Challenge { .. })
+    )
+}
+
+impl DeviationSource for ClientPool {
+    async fn get_deviation(&self, url: &str) -> Result<DeviationInfo, Error> {
+        self.run(|client| async move { client.get_deviation(url).await })
+            .await
+    }
+
+    async fn list_folder(&self, url: &str) -> Result<FolderInfo, Error> {
+        self.run(|client| async move { client.list_folder(url).await })
+            .await
+    }
+
+    async fn get_search_page(
+        &self,
+        query: &str,
+        cursor: Option<&str>,
+    ) -> Result<SearchPage, Error> {
+        self.run(|client| async move { client.get_search_page(query, cursor).await })
+            .await
+    }
+
+    async fn get_user(&self, username: &str) -> Result<User, Error> {
+        self.run(|client| async move { client.get_user(username).await })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pick() {
+        let pool = ClientPool::new(
+            [Client::new(), Client::new(), Client::new()],
+            PoolStrategy::RoundRobin,
+        );
+        let picked: Vec<_> = (0..4)
+            .map(|_| pool.pick().expect("failed to pick").0)
+            .collect();
+        assert_eq!(picked, [0, 1, 2, 0]);
+
+        pool.retire(1);
+        assert!(pool.active_len() == 2);
+        let picked: Vec<_> = (0..3)
+            .map(|_| pool.pick().expect("failed to pick").0)
+            .collect();
+        assert_eq!(picked, [0, 2, 0]);
+
+        let pool = ClientPool::new(
+            [Client::new(), Client::new()],
+            PoolStrategy::LeastRecentlyRateLimited,
+        );
+        pool.entries[0]
+            .client
+            .event_handler
+            .as_ref()
+            .expect("missing event handler")
+            .emit(&ClientEvent::RateLimited {
+                url: &crate::Url::parse("https://www.deviantart.com/").expect("invalid url"),
+                delay: std::time::Duration::from_secs(1),
+            });
+        for _ in 0..3 {
+            assert!(pool.pick().expect("failed to pick").0 == 1);
+        }
+
+        pool.retire(0);
+        pool.retire(1);
+        assert!(matches!(pool.pick(), Err(Error::NoAvailableClients)));
+    }
+
+    #[tokio::test]
+    async fn scrape_webpage_retires_on_failed_login() {
+        use crate::CredentialProvider;
+        use crate::client::test::make_html_page;
+        use crate::client::test::start_mock_server;
+
+        let body = make_html_page(include_str!("../test_data/scraped_webpage.json"));
+        let base_url = start_mock_server(body);
+
+        // Logged out pages are fine for clients that cannot log in.
+        let pool = ClientPool::new([Client::new()], PoolStrategy::RoundRobin);
+        let scraped_webpage = pool
+            .scrape_webpage(base_url.as_str())
+            .await
+            .expect("failed to scrape webpage");
+        assert!(!scraped_webpage.is_logged_in());
+        assert!(pool.active_len() == 1);
+
+        let client = Client::builder()
+            .credential_provider(CredentialProvider::new(|| None))
+            .build()
+            .expect("failed to build client");
+        let pool = ClientPool::new([client], PoolStrategy::RoundRobin);
+        let error = pool
+            .scrape_webpage(base_url.as_str())
+            .await
+            .expect_err("a logged out client should be retired");
+        assert!(matches!(error, Error::NoAvailableClients), "{error:?}");
+        assert!(pool.active_len() == 0);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::BrowseStreamItem;
+use crate::Client;
+use crate::Deviation;
+use crate::DeviationExtended;
+use crate::DeviationId;
+use crate::Error;
+use crate::FolderId;
+use crate::types::scraped_webpage_info::User;
+use std::future::Future;
+
+/// A source of DeviantArt data.
+///
+/// This abstracts over how data is fetched,
+/// so that the scraping [`Client`] can be swapped out for other backends,
+/// like the OAuth API or an offline archive.
+pub trait DeviationSource {
+    /// Get a deviation by url.
+    fn get_deviation(&self, url: &str)
+    -> impl Future<Output = Result<DeviationInfo, Error>> + Send;
+
+    /// List the contents of a gallery folder by url.
+    fn list_folder(&self, url: &str) -> impl Future<Output = Result<FolderInfo, Error>> + Send;
+
+    /// Get a page of search results.
+    ///
+    /// Pass the `next_cursor` of a [`SearchPage`] to get the page after it.
+    fn get_search_page(
+        &self,
+        query: &str,
+        cursor: Option<&str>,
+    ) -> impl Future<Output = Result<SearchPage, Error>> + Send;
+
+    /// Get a user by username.
+    fn get_user(&self, username: &str) -> impl Future<Output = Result<User, Error>> + Send;
+}
+
+/// A deviation, as returned by a [`DeviationSource`].
+#[derive(Debug)]
+pub struct DeviationInfo {
+    /// The deviation
+    pub deviation: Deviation,
+
+    /// Extended info for the deviation, if the source provides it.
+    pub deviation_extended: Option<DeviationExtended>,
+}
+
+/// A gallery folder, as returned by a [`DeviationSource`].
+#[derive(Debug)]
+pub struct FolderInfo {
+    /// The folder id
+    pub folder_id: FolderId,
+
+    /// The name of the folder
+    pub name: String,
+
+    /// The owner of the folder
+    pub owner: User,
+
+    /// The ids of every deviation in the folder
+    pub deviation_ids: Vec<DeviationId>,
+}
+
+/// A page of search results, as returned by a [`DeviationSource`].
+#[derive(Debug)]
+pub struct SearchPage {
+    /// The deviations on this page
+    pub deviations: Vec<Deviation>,
+
+    /// The items on this page that are not deviations
+    pub other_items: Vec<BrowseStreamItem>,
+
+    /// The cursor for the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+impl DeviationSource for Client {
+    async fn get_deviation(&self, url: &str) -> Result<DeviationInfo, Error> {
+        let mut scraped_webpage = self.scrape_webpage(url).await?;
+        let id = scraped_webpage
+            .get_current_deviation()
+            .ok_or(Error::MissingCurrentDeviation)?
+            .deviation_id;
+
+        let deviation = scraped_webpage
+            .take_deviation_by_id(id)
+            .ok_or(Error::MissingDeviation(id))?;
+        let deviation_extended = scraped_webpage.take_deviation_extended_by_id(id);
+
+        Ok(DeviationInfo {
+            deviation,
+            deviation_extended,
+        })
+    }
+
+    async fn list_folder(&self, url: &str) -> Result<FolderInfo, Error> {
+        let mut scraped_webpage = self.scrape_webpage(url).await?;
+        let folder_id = scraped_webpage
+            .get_current_folder_id()
+            .ok_or(Error::MissingField {
+                name: "selectedFolderId",
+            })?;
+        let stream = scraped_webpage
+            .get_folder_deviations_stream(folder_id)
+            .ok_or(Error::MissingStreams)?;
+        let mut deviation_ids: Vec<_> = stream.items.clone();
+        let mut has_more = stream.has_more;
+
+        let folder =
+            scraped_webpage
+                .get_gallery_folder_entity(folder_id)
+                .ok_or(Error::MissingField {
+                    name: "galleryFolder",
+                })?;
+        let name = folder.name.clone();
+        let owner_id = folder.owner;
+        let owner = scraped_webpage
+            .take_user_entity(owner_id)
+            .ok_or_else(|| Error::MissingUser(owner_id.to_string()))?;
+
+        while has_more {
+            let offset = u64::try_from(deviation_ids.len()).expect("too many deviations");
+            let response = self
+                .list_folder_contents(
+                    &owner.username,
+                    folder_id,
+                    offset,
+                    &scraped_webpage.config.csrf_token,
+                )
+                .await?;
+            deviation_ids.extend(
+                response
+                    .results
+                    .iter()
+                    .map(|deviation| deviation.deviation_id),
+            );
+            has_more = response.has_more;
+        }
+
+        Ok(FolderInfo {
+            folder_id,
+            name,
+            owner,
+            deviation_ids,
+        })
+    }
+
+    async fn get_search_page(
+        &self,
+        query: &str,
+        cursor: Option<&str>,
+    ) -> Result<SearchPage, Error> {
+        // Search pages have many deviations, so only parse the ones in the results.
+        let url = self.get_search_url(query, cursor)?;
+        let scraped_webpage = self.scrape_webpage_lazy(url.as_str()).await?;
+        let browse_page_stream = scraped_webpage
+            .streams
+            .as_ref()
+            .ok_or(Error::MissingStreams)?
+            .browse_page_stream
+            .as_ref()
+            .ok_or(Error::MissingBrowsePageStream)?;
+
+        let next_cursor = browse_page_stream
+            .has_more
+            .then(|| browse_page_stream.cursor.clone());
+
+        let mut deviations = Vec::with_capacity(browse_page_stream.items.len());
+        let mut other_items = Vec::new();
+        for item in browse_page_stream.items.iter() {
+            match item.deviation_id() {
+                Some(id) => {
+                    let deviation = scraped_webpage
+                        .get_deviation_by_id(id)?
+                        .ok_or(Error::MissingDeviation(id))?;
+                    deviations.push(deviation);
+                }
+                None => other_items.push(item.clone()),
+            }
+        }
+
+        Ok(SearchPage {
+            deviations,
+            other_items,
+            next_cursor,
+        })
+    }
+
+    async fn get_user(&self, username: &str) -> Result<User, Error> {
+        let url = self.endpoints.www_url(username)?;
+        let mut scraped_webpage = self.scrape_webpage(url.as_str()).await?;
+
+        let user_id = scraped_webpage
+            .entities
+            .as_ref()
+            .and_then(|entities| entities.user.as_ref())
+            .and_then(|users| {
+                users
+                    .values()
+                    .find(|user| user.username.eq_ignore_ascii_case(username))
+            })
+            .map(|user| user.user_id)
+            .ok_or_else(|| Error::MissingUser(username.into()))?;
+
+        scraped_webpage
+            .take_user_entity(user_id)
+            .ok_or_else(|| Error::MissingUser(username.into()))
+    }
+}
@@ -1,22 +1,55 @@
 /// The `Deviation` type.
 pub mod deviation;
+/// Typed ids.
+pub mod ids;
+/// The `LazyScrapedWebPageInfo` type.
+pub mod lazy_scraped_webpage_info;
+/// The `Lenient` type.
+pub mod lenient;
 /// The `ListFolderContentsResponse` type.
 pub mod list_folder_contents_response;
 /// The `Media` type.
 pub mod media;
+/// The `Nullable` type.
+pub mod nullable;
 /// The `OEmbed` type
 pub mod oembed;
 /// The `ScrapedStashInfo` type.
 pub mod scraped_stash_info;
 /// The `ScrapedWebPageInfo` type.
 pub mod scraped_webpage_info;
+/// The `StreamPageResponse` type.
+pub mod stream_page_response;
+/// The `Timestamp` type.
+pub mod timestamp;
 
 pub use self::deviation::Deviation;
+pub use self::deviation::DeviationAuthor;
+pub use self::deviation::DeviationKind;
+pub use self::deviation::UnknownKind;
+pub use self::ids::DeviationId;
+pub use self::ids::FolderId;
+pub use self::ids::IdMap;
+pub use self::ids::StashId;
+pub use self::ids::UserId;
+pub use self::lazy_scraped_webpage_info::DeviationRef;
+pub use self::lazy_scraped_webpage_info::LazyScrapedWebPageInfo;
+pub use self::lenient::Lenient;
 pub use self::list_folder_contents_response::ListFolderContentsResponse;
 pub use self::media::GetFullviewUrlError;
 pub use self::media::GetFullviewUrlOptions;
 pub use self::media::Media;
+pub use self::media::MediaType;
+pub use self::media::MediaTypeKind;
+pub use self::nullable::Nullable;
 pub use self::oembed::OEmbed;
 pub use self::scraped_stash_info::ScrapedStashInfo;
+pub use self::scraped_webpage_info::BrowseStreamItem;
+pub use self::scraped_webpage_info::ContinuableStream;
 pub use self::scraped_webpage_info::DeviationExtended;
+pub use self::scraped_webpage_info::EntityKind;
 pub use self::scraped_webpage_info::ScrapedWebPageInfo;
+pub use self::scraped_webpage_info::Stream;
+pub use self::scraped_webpage_info::StreamPosition;
+pub use self::stream_page_response::StreamPageResponse;
+pub use self::timestamp::Timestamp;
@@ -1,26 +1,38 @@
+use super::DeviationId;
 use super::GetFullviewUrlError;
 use super::GetFullviewUrlOptions;
+use super::Lenient;
 use super::Media;
+use super::Nullable;
+use super::Timestamp;
+use super::UserId;
+use super::scraped_webpage_info::User;
 use std::{collections::HashMap, path::Path};
 use url::Url;
 
 /// A Deviation
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Deviation {
-    // TODO: This is a number in a scraped deviation. Make either parse here.
-    // /// DeviantArt Author
-    // pub author: Author,
+    /// DeviantArt Author
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub author: Nullable<DeviationAuthor>,
+
     /// ?
     #[serde(rename = "blockReasons")]
     pub block_reasons: Vec<serde_json::Value>,
 
     /// Deviation ID
     #[serde(rename = "deviationId")]
-    pub deviation_id: u64,
+    pub deviation_id: DeviationId,
 
     /// Deviation Type
     #[serde(rename = "type")]
-    pub kind: String,
+    pub kind: DeviationKind,
 
     /// Image Url
     pub url: Url,
@@ -32,24 +44,43 @@ pub struct Deviation {
     pub title: String,
 
     /// Text content for literature
-    #[serde(rename = "textContent")]
-    pub text_content: Option<TextContext>,
+    #[serde(rename = "textContent", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub text_content: Nullable<TextContext>,
 
     /// Whether this is downloadable
     #[serde(rename = "isDownloadable")]
     pub is_downloadable: bool,
 
+    /// When this was published
+    #[serde(rename = "publishedTime", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub published_time: Nullable<Lenient<Timestamp>>,
+
     /// Unknown K/Vs
     #[serde(flatten)]
     pub unknown: HashMap<String, serde_json::Value>,
 }
 
 impl Deviation {
+    /// Get the username of the author, if it is embedded.
+    ///
+    /// Deviations taken from scraped pages have their authors resolved from the page's user entities.
+    pub fn author_username(&self) -> Option<&str> {
+        self.author.as_ref()?.username()
+    }
+
     /// Get the media url for this [`Deviation`].
     pub fn get_media_url(&self) -> Option<Url> {
         let mut url = self.media.base_uri.as_ref()?.clone();
         url.query_pairs_mut()
-            .append_pair("token", self.media.token.first()?);
+            .append_pair("token", self.media.tokens().first()?);
         Some(url)
     }
 
@@ -57,7 +88,7 @@ impl Deviation {
     pub fn get_download_url(&self) -> Option<Url> {
         let mut url = self.media.base_uri.as_ref()?.clone();
         url.query_pairs_mut()
-            .append_pair("token", self.media.token.get(1)?);
+            .append_pair("token", self.media.tokens().get(1)?);
         Some(url)
     }
 
@@ -71,9 +102,9 @@ impl Deviation {
 
     /// Get the GIF url for this [`Deviation`].
     pub fn get_gif_url(&self) -> Option<Url> {
-        let mut url = self.media.get_gif_media_type()?.b.clone()?;
+        let mut url = self.media.get_gif_media_type()?.b.as_ref()?.clone();
         url.query_pairs_mut()
-            .append_pair("token", self.media.token.first()?);
+            .append_pair("token", self.media.tokens().first()?);
         Some(url)
     }
 
@@ -85,17 +116,17 @@ impl Deviation {
 
     /// Whether this is an image
     pub fn is_image(&self) -> bool {
-        self.kind == "image"
+        self.kind == DeviationKind::Image
     }
 
     /// Whether this is literature
     pub fn is_literature(&self) -> bool {
-        self.kind == "literature"
+        self.kind == DeviationKind::Literature
     }
 
     /// Whether this is a film
     pub fn is_film(&self) -> bool {
-        self.kind == "film"
+        self.kind == DeviationKind::Film
     }
 
     /// Get the most "fitting" url to download an image.
@@ -135,29 +166,143 @@ impl Deviation {
     }
 }
 
+/// The kind of a [`Deviation`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[serde(from = "String")]
+#[cfg_attr(feature = "serde-serialize", serde(into = "String"))]
+#[non_exhaustive]
+pub enum DeviationKind {
+    /// An image
+    Image,
+
+    /// Literature
+    Literature,
+
+    /// A film
+    Film,
+
+    /// A journal entry
+    Journal,
+
+    /// A status update
+    Status,
+
+    /// A pdf
+    Pdf,
+
+    /// A flash animation
+    Flash,
+
+    /// An unknown kind
+    Other(UnknownKind),
+}
+
+impl DeviationKind {
+    /// Get the kind as DeviantArt names it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Image => "image",
+            Self::Literature => "literature",
+            Self::Film => "film",
+            Self::Journal => "journal",
+            Self::Status => "status",
+            Self::Pdf => "pdf",
+            Self::Flash => "flash",
+            Self::Other(kind) => kind.as_str(),
+        }
+    }
+}
+
+impl From<String> for DeviationKind {
+    fn from(kind: String) -> Self {
+        match kind.as_str() {
+            "image" => Self::Image,
+            "literature" => Self::Literature,
+            "film" => Self::Film,
+            "journal" => Self::Journal,
+            "status" => Self::Status,
+            "pdf" => Self::Pdf,
+            "flash" => Self::Flash,
+            _ => Self::Other(UnknownKind(kind)),
+        }
+    }
+}
+
+impl From<DeviationKind> for String {
+    fn from(kind: DeviationKind) -> Self {
+        match kind {
+            DeviationKind::Other(kind) => kind.0,
+            kind => kind.as_str().into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DeviationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A kind name that this library does not know.
+///
+/// This can only be made by converting from a [`String`],
+/// so a known name always becomes its own variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnknownKind(pub(crate) String);
+
+impl UnknownKind {
+    /// Get the kind as DeviantArt names it.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The author of a [`Deviation`].
+///
+/// This is a user id in a scraped deviation, which refers to a user entity on the same page.
+/// Elsewhere, like in api responses, the author is embedded.
+/// An embedded author has the same fields as a user entity.
 #[derive(Debug, serde::Deserialize)]
-pub struct Author {
-    /// is the user new
-    #[serde(rename = "isNewDeviant")]
-    pub is_new_deviant: bool,
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[serde(untagged)]
+pub enum DeviationAuthor {
+    /// The user id of the author
+    Id(UserId),
 
-    /// User UUID
-    #[serde(rename = "useridUuid")]
-    pub userid_uuid: String,
+    /// The embedded author
+    Embedded(User),
 
-    /// User icon url
-    pub usericon: Url,
+    /// An embedded author that is not a complete user, like one without a username.
+    Raw(serde_json::Value),
+}
 
-    /// User ID
-    #[serde(rename = "userId")]
-    pub user_id: u64,
+impl DeviationAuthor {
+    /// Get the user id of the author, if it is known.
+    pub fn user_id(&self) -> Option<UserId> {
+        match self {
+            Self::Id(user_id) => Some(*user_id),
+            Self::Embedded(author) => Some(author.user_id),
+            Self::Raw(author) => serde_json::from_value(author.get("userId")?.clone()).ok(),
+        }
+    }
 
-    /// Username
-    pub username: String,
+    /// Get the username of the author, if it is embedded.
+    pub fn username(&self) -> Option<&str> {
+        match self {
+            Self::Id(_) => None,
+            Self::Embedded(author) => Some(author.username.as_str()),
+            Self::Raw(author) => author.get("username")?.as_str(),
+        }
+    }
 
-    /// Unknown K/Vs
-    #[serde(flatten)]
-    pub unknown: HashMap<String, serde_json::Value>,
+    /// Get the embedded author, if it is embedded as a complete user.
+    pub fn as_embedded(&self) -> Option<&User> {
+        match self {
+            Self::Embedded(author) => Some(author),
+            Self::Id(_) | Self::Raw(_) => None,
+        }
+    }
 }
 
 /// Text Content for literature
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::num::TryFromIntError;
+use std::str::FromStr;
+
+/// Define a newtype for a numeric id.
+macro_rules! numeric_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            Hash,
+            serde::Deserialize,
+        )]
+        #[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(input: &str) -> Result<Self, Self::Err> {
+                input.parse().map(Self)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+    };
+}
+
+numeric_id!(
+    /// A deviation id
+    DeviationId
+);
+
+numeric_id!(
+    /// A user id
+    UserId
+);
+
+numeric_id!(
+    /// A sta.sh deviation id
+    StashId
+);
+
+/// A negative folder id other than -1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid folder id {0}")]
+pub struct InvalidFolderIdError(pub i64);
+
+/// An error that may occur while parsing a [`FolderId`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseFolderIdError {
+    /// The id is not an integer
+    #[error(transparent)]
+    InvalidInt(#[from] ParseIntError),
+
+    /// The id is negative, but not -1
+    #[error(transparent)]
+    InvalidFolderId(#[from] InvalidFolderIdError),
+}
+
+/// A gallery folder id.
+///
+/// DeviantArt uses a folder id of -1 for the All folder.
+/// This is serialized, displayed and parsed as -1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FolderId {
+    /// The All folder, with every deviation in the gallery
+    All,
+
+    /// A folder.
+    ///
+    /// This is not negative when parsed or converted with [`TryFrom`].
+    Id(i64),
+}
+
+impl FolderId {
+    /// Get the id as DeviantArt represents it, with -1 for the All folder.
+    pub fn as_i64(self) -> i64 {
+        match self {
+            Self::All => -1,
+            Self::Id(id) => id,
+        }
+    }
+
+    /// Returns `true` if this is the All folder.
+    pub fn is_all(self) -> bool {
+        matches!(self, Self::All)
+    }
+}
+
+impl TryFrom<i64> for FolderId {
+    type Error = InvalidFolderIdError;
+
+    fn try_from(id: i64) -> Result<Self, Self::Error> {
+        match id {
+            -1 => Ok(Self::All),
+            id if id >= 0 => Ok(Self::Id(id)),
+            id => Err(InvalidFolderIdError(id)),
+        }
+    }
+}
+
+impl TryFrom<u64> for FolderId {
+    type Error = TryFromIntError;
+
+    fn try_from(id: u64) -> Result<Self, Self::Error> {
+        i64::try_from(id).map(Self::Id)
+    }
+}
+
+impl std::fmt::Display for FolderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_i64().fmt(f)
+    }
+}
+
+impl FromStr for FolderId {
+    type Err = ParseFolderIdError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let id: i64 = input.parse()?;
+        Ok(Self::try_from(id)?)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl serde::Serialize for FolderId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_i64(self.as_i64())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for FolderId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let id = i64::deserialize(deserializer)?;
+        Self::try_from(id).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A map keyed by ids, like the entity maps of a page.
+///
+/// One odd key in an entity map should not fail a whole page,
+/// so entries whose keys are not valid ids are kept apart in [`IdMap::invalid_keys`].
+/// They are serialized back with the other entries.
+///
+/// This derefs to the map of entries with valid ids.
+#[derive(Debug, Clone)]
+pub struct IdMap<K, V> {
+    /// The entries with valid ids
+    pub entries: HashMap<K, V>,
+
+    /// The entries whose keys are not valid ids, by key
+    pub invalid_keys: HashMap<String, serde_json::Value>,
+}
+
+impl<K, V> Default for IdMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            invalid_keys: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V> std::ops::Deref for IdMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl<K, V> std::ops::DerefMut for IdMap<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.entries
+    }
+}
+
+impl<K, V> IntoIterator for IdMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::collections::hash_map::IntoIter<K, V>;
+
+    /// Iterate over the entries with valid ids.
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'de, K, V> serde::Deserialize<'de> for IdMap<K, V>
+where
+    K: FromStr + Eq + Hash,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(IdMapVisitor(PhantomData))
+    }
+}
+
+struct IdMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> serde::de::Visitor<'de> for IdMapVisitor<K, V>
+where
+    K: FromStr + Eq + Hash,
+    V: serde::Deserialize<'de>,
+{
+    type Value = IdMap<K, V>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a map keyed by ids")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut map = IdMap {
+            entries: HashMap::with_capacity(access.size_hint().unwrap_or(0)),
+            invalid_keys: HashMap::new(),
+        };
+        while let Some(key) = access.next_key::<String>()? {
+            match key.parse() {
+                Ok(id) => {
+                    map.entries.insert(id, access.next_value()?);
+                }
+                Err(_) => {
+                    map.invalid_keys.insert(key, access.next_value()?);
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<K, V> serde::Serialize for IdMap<K, V>
+where
+    K: serde::Serialize,
+    V: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        let mut map =
+            serializer.serialize_map(Some(self.entries.len() + self.invalid_keys.len()))?;
+        for (key, value) in self.entries.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        for (key, value) in self.invalid_keys.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::Nullable;
+
+    #[test]
+    fn folder_id() {
+        let ids: Vec<FolderId> = serde_json::from_str("[-1, 0, 42]").expect("failed to parse");
+        assert_eq!(ids, [FolderId::All, FolderId::Id(0), FolderId::Id(42)]);
+        assert!(serde_json::from_str::<FolderId>("-2").is_err());
+        #[cfg(feature = "serde-serialize")]
+        assert_eq!(
+            serde_json::to_string(&ids).expect("failed to serialize"),
+            "[-1,0,42]"
+        );
+
+        assert!("-1".parse::<FolderId>() == Ok(FolderId::All));
+        assert!(
+            "-5".parse::<FolderId>()
+                == Err(ParseFolderIdError::InvalidFolderId(InvalidFolderIdError(
+                    -5
+                )))
+        );
+        assert!(FolderId::Id(42).to_string() == "42");
+
+        assert!(FolderId::try_from(42_u64) == Ok(FolderId::Id(42)));
+        assert!(FolderId::try_from(u64::MAX).is_err());
+        assert!(FolderId::Id(42).as_i64() == 42);
+    }
+
+    #[test]
+    fn map_keys() {
+        let users: HashMap<UserId, u32> =
+            serde_json::from_str(r#"{"375780": 1}"#).expect("failed to parse");
+        assert!(users[&UserId(375780)] == 1);
+
+        let folders: HashMap<FolderId, u32> =
+            serde_json::from_str(r#"{"-1": 1, "42": 2}"#).expect("failed to parse");
+        assert!(folders[&FolderId::All] == 1);
+        assert!(folders[&FolderId::Id(42)] == 2);
+        #[cfg(feature = "serde-serialize")]
+        assert_eq!(
+            serde_json::to_value(&folders).expect("failed to serialize")["-1"],
+            1
+        );
+
+        assert!("17941730".parse::<DeviationId>() == Ok(DeviationId(17941730)));
+        assert!(StashId(1).to_string() == "1");
+    }
+
+    #[test]
+    fn id_map_keys() {
+        #[derive(serde::Deserialize)]
+        #[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+        struct Entities {
+            user: IdMap<UserId, u32>,
+
+            #[serde(default)]
+            #[cfg_attr(
+                feature = "serde-serialize",
+                serde(skip_serializing_if = "Nullable::is_missing")
+            )]
+            folder: Nullable<IdMap<FolderId, u32>>,
+        }
+
+        let input = r#"{"user": {"375780": 1, "abc": {"nested": [1]}, "-3": 2}}"#;
+        let entities: Entities = serde_json::from_str(input).expect("failed to parse");
+        assert!(entities.user.len() == 1);
+        assert!(entities.user[&UserId(375780)] == 1);
+        assert!(entities.user.invalid_keys.len() == 2);
+        assert!(entities.user.invalid_keys["-3"] == 2);
+        assert!(entities.folder.is_missing());
+        #[cfg(feature = "serde-serialize")]
+        assert!(
+            serde_json::to_value(&entities).expect("failed to serialize")
+                == serde_json::from_str::<serde_json::Value>(input).expect("invalid json")
+        );
+
+        let input = r#"{"user": {}, "folder": {"-1": 1, "-2": 2, "x": 3}}"#;
+        let entities: Entities = serde_json::from_str(input).expect("failed to parse");
+        let folder = entities.folder.as_ref().expect("missing folder map");
+        assert!(folder.len() == 1);
+        assert!(folder[&FolderId::All] == 1);
+        assert!(folder.invalid_keys["-2"] == 2 && folder.invalid_keys["x"] == 3);
+        #[cfg(feature = "serde-serialize")]
+        assert!(
+            serde_json::to_value(&entities).expect("failed to serialize")
+                == serde_json::from_str::<serde_json::Value>(input).expect("invalid json")
+        );
+    }
+}
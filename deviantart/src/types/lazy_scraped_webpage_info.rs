@@ -0,0 +1,300 @@
+use super::Deviation;
+use super::DeviationAuthor;
+use super::DeviationExtended;
+use super::DeviationId;
+use super::FolderId;
+use super::IdMap;
+use super::Media;
+use super::Nullable;
+use super::UserId;
+use super::scraped_webpage_info::Config;
+use super::scraped_webpage_info::DuperBrowse;
+use super::scraped_webpage_info::FromHtmlStrError;
+use super::scraped_webpage_info::GallectionSection;
+use super::scraped_webpage_info::GalleryFolder;
+use super::scraped_webpage_info::PublicSession;
+use super::scraped_webpage_info::Streams;
+use super::scraped_webpage_info::User;
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+
+/// Info scraped from a webpage, with entities parsed on access.
+///
+/// This is cheaper than [`ScrapedWebPageInfo`](super::ScrapedWebPageInfo) for pages with many entities,
+/// like search pages, when only some of them are needed.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct LazyScrapedWebPageInfo {
+    /// Page config like csrf tokens
+    #[serde(rename = "@@config")]
+    pub config: Config,
+
+    /// Unparsed page entities
+    #[serde(rename = "@@entities", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub entities: Nullable<LazyEntities>,
+
+    /// ?
+    #[serde(rename = "@@DUPERBROWSE", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub duper_browse: Nullable<DuperBrowse>,
+
+    /// Info about the current session
+    #[serde(rename = "@@publicSession")]
+    pub public_session: PublicSession,
+
+    /// Streams
+    #[serde(rename = "@@streams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub streams: Nullable<Streams>,
+
+    /// The gallery section, on gallery pages
+    #[serde(rename = "gallectionSection", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub gallection_section: Nullable<GallectionSection>,
+}
+
+/// Unparsed entities of one kind, by id.
+type RawEntityMap<K> = Nullable<IdMap<K, Box<RawValue>>>;
+
+/// Unparsed page entities, by kind and then by id.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct LazyEntities {
+    /// Deviations
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub deviation: Nullable<IdMap<DeviationId, Box<RawValue>>>,
+
+    /// Extended Deviation Info
+    #[serde(rename = "deviationExtended", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub deviation_extended: Nullable<IdMap<DeviationId, Box<RawValue>>>,
+
+    /// Gallery folders
+    #[serde(rename = "galleryFolder", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub gallery_folder: Nullable<IdMap<FolderId, Box<RawValue>>>,
+
+    /// Users
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub user: Nullable<IdMap<UserId, Box<RawValue>>>,
+}
+
+/// A deviation that borrows from a [`LazyScrapedWebPageInfo`].
+///
+/// This only has the most commonly used fields.
+/// Use [`LazyScrapedWebPageInfo::get_deviation_by_id`] for everything else.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct DeviationRef<'a> {
+    /// Deviation ID
+    #[serde(rename = "deviationId")]
+    pub deviation_id: DeviationId,
+
+    /// Deviation Type
+    #[serde(rename = "type", borrow)]
+    pub kind: Cow<'a, str>,
+
+    /// The deviation page url
+    #[serde(borrow)]
+    pub url: Cow<'a, str>,
+
+    /// Title
+    #[serde(borrow)]
+    pub title: Cow<'a, str>,
+
+    /// Whether this is downloadable
+    #[serde(rename = "isDownloadable")]
+    pub is_downloadable: bool,
+
+    /// Unparsed media info
+    #[serde(borrow)]
+    pub media: &'a RawValue,
+}
+
+impl DeviationRef<'_> {
+    /// Parse the media info.
+    pub fn parse_media(&self) -> Result<Media, serde_json::Error> {
+        serde_json::from_str(self.media.get())
+    }
+}
+
+impl LazyScrapedWebPageInfo {
+    /// Parse this from a html string
+    pub fn from_html_str(input: &str) -> Result<Self, FromHtmlStrError> {
+        super::scraped_webpage_info::extract_initial_state(input)
+    }
+
+    /// Returns `true` if logged in
+    pub fn is_logged_in(&self) -> bool {
+        self.public_session.is_logged_in
+    }
+
+    /// Get the current deviation's id
+    pub fn get_current_deviation_id(&self) -> Option<DeviationId> {
+        match &self
+            .duper_browse
+            .as_ref()?
+            .root_stream
+            .as_ref()?
+            .current_open_item
+        {
+            serde_json::Value::Number(n) => n.as_u64().map(DeviationId),
+            serde_json::Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Get the ids of the deviations on this page.
+    pub fn deviation_ids(&self) -> impl Iterator<Item = DeviationId> + '_ {
+        self.entities
+            .as_ref()
+            .and_then(|entities| entities.deviation.as_ref())
+            .into_iter()
+            .flat_map(|deviations| deviations.keys())
+            .copied()
+    }
+
+    /// Get the unparsed json of an entity by kind and id.
+    fn get_raw_entity<K>(
+        &self,
+        kind: fn(&LazyEntities) -> &RawEntityMap<K>,
+        id: K,
+    ) -> Option<&RawValue>
+    where
+        K: Eq + std::hash::Hash + 'static,
+    {
+        kind(self.entities.as_ref()?)
+            .as_ref()?
+            .get(&id)
+            .map(|raw| &**raw)
+    }
+
+    /// Parse a deviation by id, if it exists.
+    ///
+    /// The author is resolved from the page's user entities, if possible.
+    pub fn get_deviation_by_id(
+        &self,
+        id: DeviationId,
+    ) -> Result<Option<Deviation>, serde_json::Error> {
+        let Some(raw) = self.get_raw_entity(|entities| &entities.deviation, id) else {
+            return Ok(None);
+        };
+        let mut deviation: Deviation = serde_json::from_str(raw.get())?;
+        if let Nullable::Value(DeviationAuthor::Id(user_id)) = deviation.author
+            && let Some(user) = self.get_user_entity(user_id)?
+        {
+            deviation.author = Nullable::Value(DeviationAuthor::Embedded(user));
+        }
+
+        Ok(Some(deviation))
+    }
+
+    /// Parse a deviation by id into a borrowed [`DeviationRef`], if it exists
+    pub fn get_deviation_ref_by_id(
+        &self,
+        id: DeviationId,
+    ) -> Result<Option<DeviationRef<'_>>, serde_json::Error> {
+        self.get_raw_entity(|entities| &entities.deviation, id)
+            .map(|raw| serde_json::from_str(raw.get()))
+            .transpose()
+    }
+
+    /// Parse a deviation's extended info by id, if it exists
+    pub fn get_deviation_extended_by_id(
+        &self,
+        id: DeviationId,
+    ) -> Result<Option<DeviationExtended>, serde_json::Error> {
+        self.get_raw_entity(|entities| &entities.deviation_extended, id)
+            .map(|raw| serde_json::from_str(raw.get()))
+            .transpose()
+    }
+
+    /// Parse a gallery folder entity by id, if it exists
+    pub fn get_gallery_folder_entity(
+        &self,
+        folder_id: FolderId,
+    ) -> Result<Option<GalleryFolder>, serde_json::Error> {
+        self.get_raw_entity(|entities| &entities.gallery_folder, folder_id)
+            .map(|raw| serde_json::from_str(raw.get()))
+            .transpose()
+    }
+
+    /// Parse a user entity by id, if it exists
+    pub fn get_user_entity(&self, user_id: UserId) -> Result<Option<User>, serde_json::Error> {
+        self.get_raw_entity(|entities| &entities.user, user_id)
+            .map(|raw| serde_json::from_str(raw.get()))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ScrapedWebPageInfo;
+
+    const SCRAPED_WEBPAGE: &str = include_str!("../../test_data/scraped_webpage.json");
+
+    #[test]
+    fn parse_lazy_scraped_webpage() {
+        let lazy: LazyScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse lazy webpage info");
+        let eager: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse webpage info");
+
+        let id = lazy
+            .get_current_deviation_id()
+            .expect("missing current deviation id");
+        let deviation = lazy
+            .get_deviation_by_id(id)
+            .expect("failed to parse deviation")
+            .expect("missing deviation");
+        let expected = eager
+            .get_current_deviation()
+            .expect("missing current deviation");
+        assert!(deviation.deviation_id == expected.deviation_id);
+        assert!(deviation.title == expected.title);
+        assert!(deviation.author_username() == Some("zilla774"));
+
+        let deviation_ref = lazy
+            .get_deviation_ref_by_id(id)
+            .expect("failed to parse deviation ref")
+            .expect("missing deviation");
+        assert!(deviation_ref.title == expected.title);
+        assert!(deviation_ref.url == expected.url.as_str());
+        deviation_ref.parse_media().expect("failed to parse media");
+
+        assert!(lazy.deviation_ids().count() == eager.entities.as_ref().unwrap().deviation.len());
+        assert!(
+            lazy.get_deviation_extended_by_id(id)
+                .expect("failed to parse deviation extended")
+                .is_some()
+        );
+    }
+}
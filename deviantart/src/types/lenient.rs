@@ -0,0 +1,163 @@
+use super::Nullable;
+
+/// A value whose shape has not been checked against live pages.
+///
+/// A value with an unexpected shape is kept as raw json instead of failing the whole page.
+/// Either way, this serializes back to what was read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lenient<T> {
+    /// The value had the expected shape
+    Typed(T),
+
+    /// The value had an unexpected shape
+    Raw(serde_json::Value),
+}
+
+impl<T> Lenient<T> {
+    /// Get the typed value, if the value had the expected shape.
+    pub fn typed(&self) -> Option<&T> {
+        match self {
+            Self::Typed(value) => Some(value),
+            Self::Raw(_) => None,
+        }
+    }
+
+    /// Get the raw value, if the value had an unexpected shape.
+    pub fn raw(&self) -> Option<&serde_json::Value> {
+        match self {
+            Self::Typed(_) => None,
+            Self::Raw(value) => Some(value),
+        }
+    }
+
+    /// Convert into the typed value, if the value had the expected shape.
+    pub fn into_typed(self) -> Option<T> {
+        match self {
+            Self::Typed(value) => Some(value),
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+impl Lenient<u64> {
+    /// Get the count, also accepting numeric strings like "1,234".
+    pub fn count(&self) -> Option<u64> {
+        match self {
+            Self::Typed(value) => Some(*value),
+            Self::Raw(serde_json::Value::String(value)) => {
+                value.replace(',', "").trim().parse().ok()
+            }
+            Self::Raw(_) => None,
+        }
+    }
+}
+
+impl<T> Nullable<Lenient<T>> {
+    /// Get the typed value, if there is one and it had the expected shape.
+    pub fn typed(&self) -> Option<&T> {
+        self.as_ref().and_then(Lenient::typed)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Lenient<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: serde_json::Value = serde::Deserialize::deserialize(deserializer)?;
+        Ok(match T::deserialize(&value) {
+            Ok(typed) => Self::Typed(typed),
+            Err(_) => Self::Raw(value),
+        })
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<T> serde::Serialize for Lenient<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Typed(value) => value.serialize(serializer),
+            Self::Raw(value) => value.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    #[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+    struct Fields {
+        #[serde(default)]
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(skip_serializing_if = "Nullable::is_missing")
+        )]
+        tags: Nullable<Lenient<Vec<String>>>,
+
+        #[serde(default)]
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(skip_serializing_if = "Nullable::is_missing")
+        )]
+        views: Nullable<Lenient<u64>>,
+
+        #[serde(default)]
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(skip_serializing_if = "Nullable::is_missing")
+        )]
+        iso: Nullable<Lenient<String>>,
+    }
+
+    #[test]
+    fn lenient_fields() {
+        let fields: Fields = serde_json::from_str(r#"{"tags": ["a"], "views": 3, "iso": "100"}"#)
+            .expect("failed to parse");
+        assert!(fields.tags.typed().map(Vec::as_slice) == Some(&["a".to_string()][..]));
+        assert!(fields.views.typed() == Some(&3));
+        assert!(fields.iso.typed().map(String::as_str) == Some("100"));
+
+        let fields: Fields = serde_json::from_str(r#"{"tags": "a", "views": "1,234", "iso": 100}"#)
+            .expect("failed to parse");
+        assert!(fields.tags.typed().is_none());
+        assert!(fields.tags.as_ref().and_then(Lenient::raw) == Some(&serde_json::json!("a")));
+        assert!(fields.views.typed().is_none());
+        assert!(fields.views.as_ref().and_then(Lenient::count) == Some(1234));
+        assert!(fields.iso.typed().is_none());
+
+        let fields: Fields = serde_json::from_str(r#"{"tags": null, "views": -1, "iso": {}}"#)
+            .expect("failed to parse");
+        assert!(fields.tags.is_null());
+        assert!(fields.views.as_ref().and_then(Lenient::count).is_none());
+        assert!(fields.iso.as_ref().and_then(Lenient::raw) == Some(&serde_json::json!({})));
+
+        let fields: Fields = serde_json::from_str("{}").expect("failed to parse");
+        assert!(fields.tags.is_missing() && fields.views.is_missing() && fields.iso.is_missing());
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn round_trip() {
+        for input in [
+            r#"{"tags":["a"],"views":3,"iso":"100"}"#,
+            r#"{"tags":"a","views":"1,234","iso":100}"#,
+            r#"{"tags":null,"views":-1,"iso":{}}"#,
+            r#"{"views":1.5,"iso":true}"#,
+            r#"{}"#,
+        ] {
+            let fields: Fields = serde_json::from_str(input).expect("failed to parse");
+            assert!(serde_json::to_string(&fields).expect("failed to serialize") == input);
+        }
+    }
+}
@@ -1,15 +1,21 @@
 use super::Deviation;
+use super::Nullable;
 use std::collections::HashMap;
 
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct ListFolderContentsResponse {
     /// Whether this has more
     #[serde(rename = "hasMore")]
     pub has_more: bool,
 
     /// The next offset
-    #[serde(rename = "nextOffset")]
-    pub next_offset: Option<u64>,
+    #[serde(rename = "nextOffset", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub next_offset: Nullable<u64>,
 
     /// results
     pub results: Vec<Deviation>,
@@ -1,3 +1,5 @@
+use super::Nullable;
+use super::UnknownKind;
 use std::collections::HashMap;
 use std::fmt::Write;
 use url::Url;
@@ -212,10 +214,15 @@ fn create_fullview_path(
 
 /// DeviantArt [`DeviationMedia`] media type.
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct MediaType {
     /// The content. A uri used with base_uri.
-    #[serde(rename = "c")]
-    pub content: Option<String>,
+    #[serde(rename = "c", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub content: Nullable<String>,
 
     /// Image height
     #[serde(rename = "h")]
@@ -225,7 +232,7 @@ pub struct MediaType {
     // // pub r: u64,
     /// The kind of media
     #[serde(rename = "t")]
-    pub kind: String,
+    pub kind: MediaTypeKind,
 
     /// Image Width
     #[serde(rename = "w")]
@@ -234,7 +241,12 @@ pub struct MediaType {
     // /// ?
     // // pub f: Option<u64>,
     /// ?
-    pub b: Option<Url>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub b: Nullable<Url>,
 
     /// Unknown K/Vs
     #[serde(flatten)]
@@ -244,17 +256,130 @@ pub struct MediaType {
 impl MediaType {
     /// Whether this is the fullview
     pub fn is_fullview(&self) -> bool {
-        self.kind == "fullview"
+        self.kind == MediaTypeKind::Fullview
     }
 
     /// Whether this is a gif
     pub fn is_gif(&self) -> bool {
-        self.kind == "gif"
+        self.kind == MediaTypeKind::Gif
     }
 
     /// Whether this is a video
     pub fn is_video(&self) -> bool {
-        self.kind == "video"
+        self.kind == MediaTypeKind::Video
+    }
+}
+
+/// The kind of a [`MediaType`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[serde(from = "String")]
+#[cfg_attr(feature = "serde-serialize", serde(into = "String"))]
+#[non_exhaustive]
+pub enum MediaTypeKind {
+    /// The full size image
+    Fullview,
+
+    /// A preview image
+    Preview,
+
+    /// A preview image for social media embeds
+    SocialPreview,
+
+    /// An animated gif
+    Gif,
+
+    /// A video
+    Video,
+
+    /// A 150px thumbnail, "150"
+    Thumb150,
+
+    /// A thumbnail that is 200px high, "200H"
+    Thumb200H,
+
+    /// A thumbnail that is 300px wide, "300W"
+    Thumb300W,
+
+    /// A 250px thumbnail, "250T"
+    Thumb250T,
+
+    /// A 350px thumbnail, "350T"
+    Thumb350T,
+
+    /// A 400px thumbnail, "400T"
+    Thumb400T,
+
+    /// A 360p film, "360p"
+    Film360p,
+
+    /// A 720p film, "720p"
+    Film720p,
+
+    /// A 1080p film, "1080p"
+    Film1080p,
+
+    /// An unknown kind
+    Other(UnknownKind),
+}
+
+impl MediaTypeKind {
+    /// Get the kind as DeviantArt names it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Fullview => "fullview",
+            Self::Preview => "preview",
+            Self::SocialPreview => "social_preview",
+            Self::Gif => "gif",
+            Self::Video => "video",
+            Self::Thumb150 => "150",
+            Self::Thumb200H => "200H",
+            Self::Thumb300W => "300W",
+            Self::Thumb250T => "250T",
+            Self::Thumb350T => "350T",
+            Self::Thumb400T => "400T",
+            Self::Film360p => "360p",
+            Self::Film720p => "720p",
+            Self::Film1080p => "1080p",
+            Self::Other(kind) => kind.as_str(),
+        }
+    }
+}
+
+impl From<String> for MediaTypeKind {
+    fn from(kind: String) -> Self {
+        match kind.as_str() {
+            "fullview" => Self::Fullview,
+            "preview" => Self::Preview,
+            "social_preview" => Self::SocialPreview,
+            "gif" => Self::Gif,
+            "video" => Self::Video,
+            "150" => Self::Thumb150,
+            "200H" => Self::Thumb200H,
+            "300W" => Self::Thumb300W,
+            "250T" => Self::Thumb250T,
+            "350T" => Self::Thumb350T,
+            "400T" => Self::Thumb400T,
+            "360p" => Self::Film360p,
+            "720p" => Self::Film720p,
+            "1080p" => Self::Film1080p,
+            _ => Self::Other(UnknownKind(kind)),
+        }
+    }
+}
+
+impl From<MediaTypeKind> for String {
+    fn from(kind: MediaTypeKind) -> Self {
+        match kind {
+            MediaTypeKind::Other(kind) => kind.0,
+            kind => kind.as_str().into(),
+        }
+    }
+}
+
+impl std::fmt::Display for MediaTypeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
@@ -262,21 +387,36 @@ impl MediaType {
 ///
 /// Needed to create image urls.
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Media {
     /// The base uri
-    #[serde(rename = "baseUri")]
-    pub base_uri: Option<Url>,
-
-    /// Image token
+    #[serde(rename = "baseUri", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub base_uri: Nullable<Url>,
+
+    /// Image tokens.
+    ///
+    /// This is missing for some media. Use [`Media::tokens`] to get an empty slice instead.
     #[serde(default)]
-    pub token: Vec<String>,
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub token: Nullable<Vec<String>>,
 
     /// Types
     pub types: Vec<MediaType>,
 
     /// Pretty Name
-    #[serde(rename = "prettyName")]
-    pub pretty_name: Option<String>,
+    #[serde(rename = "prettyName", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub pretty_name: Nullable<String>,
 
     /// Unknown K/Vs
     #[serde(flatten)]
@@ -284,6 +424,11 @@ pub struct Media {
 }
 
 impl Media {
+    /// Get the image tokens, or an empty slice if they are missing.
+    pub fn tokens(&self) -> &[String] {
+        self.token.as_deref().unwrap_or_default()
+    }
+
     /// Try to get the fullview [`MediaType`].
     pub fn get_fullview_media_type(&self) -> Option<&MediaType> {
         self.types.iter().find(|t| t.is_fullview())
@@ -336,7 +481,7 @@ impl Media {
         // As such, this part is optional.
         // So far, a token is allowed to be missing when the "content" section of the fullview data is missing
         // Correct this if these assumptions are wrong.
-        if let Some(token) = self.token.first() {
+        if let Some(token) = self.tokens().first() {
             url.query_pairs_mut().append_pair("token", token);
         }
 
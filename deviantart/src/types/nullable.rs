@@ -0,0 +1,177 @@
+/// An optional field that remembers whether it was missing or `null`.
+///
+/// DeviantArt leaves out some fields and sends others as `null`.
+/// Keeping the difference lets a deserialized value serialize back to what was read.
+/// Fields of this type need `#[serde(default)]`, so that a missing field becomes [`Nullable::Missing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Nullable<T> {
+    /// The field was missing
+    #[default]
+    Missing,
+
+    /// The field was `null`
+    Null,
+
+    /// The field had a value
+    Value(T),
+}
+
+impl<T> Nullable<T> {
+    /// Returns `true` if there is a value.
+    pub fn is_some(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+
+    /// Returns `true` if the field was missing or `null`.
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// Returns `true` if the field was `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns `true` if the field was missing.
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+
+    /// Get a reference to the value, if there is one.
+    pub fn as_ref(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Missing | Self::Null => None,
+        }
+    }
+
+    /// Get a mutable reference to the value, if there is one.
+    pub fn as_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Missing | Self::Null => None,
+        }
+    }
+
+    /// Get a dereferenced value, if there is one.
+    pub fn as_deref(&self) -> Option<&T::Target>
+    where
+        T: std::ops::Deref,
+    {
+        self.as_ref().map(|value| &**value)
+    }
+
+    /// Convert into an [`Option`], forgetting whether the field was missing or `null`.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Missing | Self::Null => None,
+        }
+    }
+
+    /// Take the value out, leaving the field `null`.
+    pub fn take(&mut self) -> Option<T> {
+        match std::mem::replace(self, Self::Null) {
+            Self::Value(value) => Some(value),
+            Self::Missing => {
+                *self = Self::Missing;
+                None
+            }
+            Self::Null => None,
+        }
+    }
+}
+
+impl<T> IntoIterator for Nullable<T> {
+    type Item = T;
+    type IntoIter = std::option::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_option().into_iter()
+    }
+}
+
+impl<T> From<T> for Nullable<T> {
+    fn from(value: T) -> Self {
+        Self::Value(value)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Nullable<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: Option<T> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(match value {
+            Some(value) => Self::Value(value),
+            None => Self::Null,
+        })
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<T> serde::Serialize for Nullable<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Value(value) => serializer.serialize_some(value),
+            Self::Missing | Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize)]
+    #[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+    struct Fields {
+        #[serde(default)]
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(skip_serializing_if = "Nullable::is_missing")
+        )]
+        a: Nullable<u64>,
+
+        #[serde(default)]
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(skip_serializing_if = "Nullable::is_missing")
+        )]
+        b: Nullable<u64>,
+    }
+
+    #[test]
+    fn nullable() {
+        let fields: Fields = serde_json::from_str(r#"{"a": null}"#).expect("failed to parse");
+        assert!(fields.a == Nullable::Null);
+        assert!(fields.b == Nullable::Missing);
+        assert!(fields.a.is_none() && fields.b.is_none());
+
+        let mut fields: Fields = serde_json::from_str(r#"{"a": 1}"#).expect("failed to parse");
+        assert!(fields.a.as_ref() == Some(&1));
+        assert!(fields.a.take() == Some(1));
+        assert!(fields.a == Nullable::Null);
+        assert!(fields.b.take().is_none());
+        assert!(fields.b == Nullable::Missing);
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn round_trip() {
+        for input in [r#"{}"#, r#"{"a":null}"#, r#"{"a":1,"b":null}"#] {
+            let fields: Fields = serde_json::from_str(input).expect("failed to parse");
+            assert!(serde_json::to_string(&fields).expect("failed to serialize") == input);
+        }
+    }
+}
@@ -1,14 +1,21 @@
+use super::Nullable;
 use std::collections::HashMap;
 use url::Url;
 
 /// DeviantArt OEmbed
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct OEmbed {
     /// Url of the asset
     pub url: Url,
 
     /// Url of the thumbnail
-    pub thumbnail_url: Option<Url>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub thumbnail_url: Nullable<Url>,
 
     /// Title
     pub title: String,
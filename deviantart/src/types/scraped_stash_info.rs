@@ -1,8 +1,11 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use super::Nullable;
+use super::StashId;
 use std::collections::HashMap;
 use url::Url;
 
+/// The start of the pageData variable.
+const PAGE_DATA_MARKER: &str = "deviantART.pageData=";
+
 /// An error that may occur while parsing a [`ScrapedStashInfo`] from a html str.
 #[derive(Debug, thiserror::Error)]
 pub enum FromHtmlStrError {
@@ -17,15 +20,21 @@ pub enum FromHtmlStrError {
 
 /// Scraped info from a sta.sh link
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct ScrapedStashInfo {
     /// Csrf token
     pub csrf: String,
 
     /// ?
-    pub deviationid: u64,
+    pub deviationid: StashId,
 
     /// Present only if it is a video
-    pub film: Option<Film>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub film: Nullable<Film>,
 
     /// The width
     pub deviation_width: u64,
@@ -41,15 +50,16 @@ pub struct ScrapedStashInfo {
 impl ScrapedStashInfo {
     /// Parse this from a html str
     pub fn from_html_str(input: &str) -> Result<Self, FromHtmlStrError> {
-        static REGEX: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r#"deviantART.pageData=(.*);"#).expect("invalid `scrape_stash_info` regex")
-        });
+        let start = input
+            .find(PAGE_DATA_MARKER)
+            .ok_or(FromHtmlStrError::MissingPageData)?
+            + PAGE_DATA_MARKER.len();
 
-        let capture = REGEX
-            .captures(input)
-            .and_then(|captures| captures.get(1))
-            .ok_or(FromHtmlStrError::MissingPageData)?;
-        let scraped_stash: ScrapedStashInfo = serde_json::from_str(capture.as_str())?;
+        // Deserialize the object directly from the page, stopping at its end.
+        let scraped_stash = serde_json::Deserializer::from_str(&input[start..])
+            .into_iter::<ScrapedStashInfo>()
+            .next()
+            .ok_or(FromHtmlStrError::MissingPageData)??;
 
         Ok(scraped_stash)
     }
@@ -57,6 +67,7 @@ impl ScrapedStashInfo {
 
 /// Film data from a sta.sh link
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Film {
     /// Video sizes
     pub sizes: HashMap<String, Size>,
@@ -75,6 +86,7 @@ impl Film {
 
 /// Film size
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Size {
     /// Video height
     pub height: u32,
@@ -89,3 +101,24 @@ pub struct Size {
     #[serde(flatten)]
     pub unknown: HashMap<String, serde_json::Value>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_html_str() {
+        let page = r#"<script>deviantART.pageData={"csrf":"token","deviationid":1,"film":null,"deviation_width":2,"deviation_height":3,"title":"a;b"};var other = {};</script>"#;
+        let scraped_stash = ScrapedStashInfo::from_html_str(page).expect("failed to parse");
+        assert!(scraped_stash.csrf == "token");
+        assert!(scraped_stash.deviation_height == 3);
+        assert!(scraped_stash.unknown["title"] == "a;b");
+
+        let error =
+            ScrapedStashInfo::from_html_str("<html></html>").expect_err("parsed an empty page");
+        assert!(
+            matches!(error, FromHtmlStrError::MissingPageData),
+            "{error:?}"
+        );
+    }
+}
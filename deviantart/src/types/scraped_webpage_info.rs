@@ -1,10 +1,23 @@
 use super::Deviation;
+use super::DeviationAuthor;
+use super::DeviationId;
+use super::FolderId;
+use super::IdMap;
+use super::Lenient;
 use super::Media;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use super::Nullable;
+use super::UserId;
+use crate::interstitial::Interstitial;
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use url::Url;
 
+pub use crate::js_string::InvalidEscapeError;
+
+/// The start of the initial state string literal.
+const INITIAL_STATE_MARKER: &str = "window.__INITIAL_STATE__ = JSON.parse(\"";
+
 /// An error that may occur while parsing a [`ScrapedWebPageInfo`] from a html string.
 #[derive(Debug, thiserror::Error)]
 pub enum FromHtmlStrError {
@@ -12,50 +25,174 @@ pub enum FromHtmlStrError {
     #[error("missing initial state")]
     MissingInitialState,
 
+    /// The initial state string has an invalid escape sequence
+    #[error("invalid initial state string")]
+    InvalidEscape(#[from] InvalidEscapeError),
+
     /// Failed to parse some state
     #[error(transparent)]
     InvalidJson(#[from] serde_json::Error),
+
+    /// The page is a bot challenge or captcha
+    #[error("got a challenge page")]
+    Challenge {
+        /// The raw page
+        page: String,
+    },
+
+    /// The page is a maintenance notice
+    #[error("got a maintenance page")]
+    Maintenance {
+        /// The raw page
+        page: String,
+    },
+
+    /// The page is a not found or deleted notice
+    #[error("got a not found page")]
+    NotFound {
+        /// The raw page
+        page: String,
+    },
+
+    /// The page hides mature content from logged out users
+    #[error("got a mature content page")]
+    MatureContentGate {
+        /// The raw page
+        page: String,
+    },
+}
+
+impl FromHtmlStrError {
+    /// Get the raw page, if this error is for an interstitial page.
+    pub fn page(&self) -> Option<&str> {
+        match self {
+            Self::Challenge { page }
+            | Self::Maintenance { page }
+            | Self::NotFound { page }
+            | Self::MatureContentGate { page } => Some(page),
+            Self::MissingInitialState | Self::InvalidEscape(_) | Self::InvalidJson(_) => None,
+        }
+    }
+
+    /// Make the error for a page without an initial state.
+    fn from_missing_initial_state(input: &str) -> Self {
+        let page = input.to_string();
+        match Interstitial::detect(input) {
+            Some(Interstitial::Challenge) => Self::Challenge { page },
+            Some(Interstitial::Maintenance) => Self::Maintenance { page },
+            Some(Interstitial::Deleted | Interstitial::NotFound) => Self::NotFound { page },
+            Some(Interstitial::MatureContentGate) => Self::MatureContentGate { page },
+            None => Self::MissingInitialState,
+        }
+    }
+}
+
+/// Find the initial state in a html string and deserialize it.
+pub(crate) fn extract_initial_state<T>(input: &str) -> Result<T, FromHtmlStrError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    thread_local! {
+        /// A buffer for the unescaped initial state, reused between calls.
+        static BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+    }
+
+    let start = input
+        .find(INITIAL_STATE_MARKER)
+        .ok_or_else(|| FromHtmlStrError::from_missing_initial_state(input))?
+        + INITIAL_STATE_MARKER.len();
+
+    BUFFER.with_borrow_mut(|buffer| {
+        let json = crate::js_string::unescape_quoted(&input[start..], buffer)?
+            .ok_or_else(|| FromHtmlStrError::from_missing_initial_state(input))?;
+        Ok(serde_json::from_str(json)?)
+    })
 }
 
 /// Info scraped from a deviation url
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct ScrapedWebPageInfo {
     /// Page config like csrf tokens
     #[serde(rename = "@@config")]
     pub config: Config,
 
     /// Deviations extended deviations maybe?
-    #[serde(rename = "@@entities")]
-    pub entities: Option<Entities>,
+    #[serde(rename = "@@entities", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub entities: Nullable<Entities>,
 
     /// ?
-    #[serde(rename = "@@DUPERBROWSE")]
-    pub duper_browse: Option<DuperBrowse>,
+    #[serde(rename = "@@DUPERBROWSE", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub duper_browse: Nullable<DuperBrowse>,
 
     /// Info about the current session
     #[serde(rename = "@@publicSession")]
     pub public_session: PublicSession,
 
     /// Streams
-    #[serde(rename = "@@streams")]
-    pub streams: Option<Streams>,
+    #[serde(rename = "@@streams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub streams: Nullable<Streams>,
 
     /// Needed for login.
     ///
     /// Note that this is a different csrf token from the config struct.
-    #[serde(rename = "csrfToken")]
-    pub csrf_token: Option<Box<str>>,
-
-    #[serde(rename = "gallectionSection")]
-    pub gallection_section: Option<GallectionSection>,
+    #[serde(rename = "csrfToken", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub csrf_token: Nullable<Box<str>>,
+
+    #[serde(rename = "gallectionSection", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub gallection_section: Nullable<GallectionSection>,
 
     /// Needed for login.
-    #[serde(rename = "luToken")]
-    pub lu_token: Option<Box<str>>,
+    #[serde(rename = "luToken", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub lu_token: Nullable<Box<str>>,
 
     /// Needed for login.
-    #[serde(rename = "luToken2")]
-    pub lu_token2: Option<Box<str>>,
+    #[serde(rename = "luToken2", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub lu_token2: Nullable<Box<str>>,
+
+    /// The state of the login form, on login pages.
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub login: Nullable<LoginState>,
+
+    /// The login step, on login pages.
+    #[serde(rename = "authMode", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub auth_mode: Nullable<String>,
 
     /// Unknown data
     #[serde(flatten)]
@@ -65,21 +202,7 @@ pub struct ScrapedWebPageInfo {
 impl ScrapedWebPageInfo {
     /// Parse this from a html string
     pub fn from_html_str(input: &str) -> Result<Self, FromHtmlStrError> {
-        static REGEX: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r#"window\.__INITIAL_STATE__ = JSON\.parse\("(.*)"\);"#).unwrap()
-        });
-
-        let capture = REGEX
-            .captures(input)
-            .and_then(|captures| captures.get(1))
-            .ok_or(FromHtmlStrError::MissingInitialState)?;
-        // TODO: Escape properly
-        let capture = capture
-            .as_str()
-            .replace("\\\"", "\"")
-            .replace("\\'", "'")
-            .replace("\\\\", "\\");
-        Ok(serde_json::from_str(&capture)?)
+        extract_initial_state(input)
     }
 
     /// Returns `true` if logged in
@@ -88,71 +211,100 @@ impl ScrapedWebPageInfo {
     }
 
     /// Get the current deviation's id
-    pub fn get_current_deviation_id(&self) -> Option<&serde_json::Value> {
-        Some(
-            &self
-                .duper_browse
-                .as_ref()?
-                .root_stream
-                .as_ref()?
-                .current_open_item,
-        )
+    pub fn get_current_deviation_id(&self) -> Option<DeviationId> {
+        match &self
+            .duper_browse
+            .as_ref()?
+            .root_stream
+            .as_ref()?
+            .current_open_item
+        {
+            serde_json::Value::Number(n) => n.as_u64().map(DeviationId),
+            serde_json::Value::String(s) => s.parse().ok(),
+            _ => None,
+        }
     }
 
     /// Get the [`Deviation`] for this page.
+    ///
+    /// The author is usually a user id here. Use [`ScrapedWebPageInfo::get_deviation_author`] to look it up.
     pub fn get_current_deviation(&self) -> Option<&Deviation> {
-        let id = self.get_current_deviation_id()?;
-        let id = match id {
-            serde_json::Value::Number(n) => n.as_u64()?,
-            serde_json::Value::String(s) => s.parse().ok()?,
-            _ => return None,
-        };
-        self.get_deviation_by_id(id)
+        self.get_deviation_by_id(self.get_current_deviation_id()?)
     }
 
     /// Get the [`DeviationExtended`] for this page.
     pub fn get_current_deviation_extended(&self) -> Option<&DeviationExtended> {
         let id = self.get_current_deviation_id()?;
-        let mut key_buffer = itoa::Buffer::new();
-        let key = match id {
-            serde_json::Value::Number(n) => {
-                let n = n.as_u64()?;
-                key_buffer.format(n)
-            }
-            serde_json::Value::String(s) => s,
-            _ => return None,
-        };
         self.entities
             .as_ref()?
             .deviation_extended
             .as_ref()?
-            .get(key)
+            .get(&id)
+    }
+
+    /// Get a deviation by id, if it exists.
+    ///
+    /// The author is usually a user id here. Use [`ScrapedWebPageInfo::get_deviation_author`] to look it up.
+    pub fn get_deviation_by_id(&self, id: DeviationId) -> Option<&Deviation> {
+        self.entities.as_ref()?.deviation.get(&id)
+    }
+
+    /// Take a deviation by id, if it exists.
+    ///
+    /// The author is resolved from the page's user entities, if possible.
+    pub fn take_deviation_by_id(&mut self, id: DeviationId) -> Option<Deviation> {
+        let mut deviation = self.entities.as_mut()?.deviation.remove(&id)?;
+        self.resolve_deviation_author(&mut deviation);
+        Some(deviation)
+    }
+
+    /// Get the author of a deviation, from the deviation if it is embedded or from the page's user entities.
+    pub fn get_deviation_author<'a>(&'a self, deviation: &'a Deviation) -> Option<&'a User> {
+        match deviation.author.as_ref()? {
+            DeviationAuthor::Embedded(user) => Some(user),
+            author => self.get_user_entity(author.user_id()?),
+        }
     }
 
-    /// Get a deviation by id, if it exists
-    pub fn get_deviation_by_id(&self, id: u64) -> Option<&Deviation> {
-        let mut key_buffer = itoa::Buffer::new();
-        self.entities.as_ref()?.deviation.get(key_buffer.format(id))
+    /// Get the username of the author of a deviation, like [`ScrapedWebPageInfo::get_deviation_author`].
+    pub fn get_deviation_author_username<'a>(
+        &'a self,
+        deviation: &'a Deviation,
+    ) -> Option<&'a str> {
+        self.get_deviation_author(deviation)
+            .map(|user| user.username.as_str())
     }
 
-    /// Take a deviation by id, if it exists
-    pub fn take_deviation_by_id(&mut self, id: u64) -> Option<Deviation> {
-        let mut key_buffer = itoa::Buffer::new();
+    /// Replace the author id of a deviation with the author, from the page's user entities.
+    ///
+    /// This does nothing if the author is already embedded, or if the user entity is missing.
+    pub fn resolve_deviation_author(&self, deviation: &mut Deviation) {
+        let Nullable::Value(DeviationAuthor::Id(user_id)) = deviation.author else {
+            return;
+        };
+        if let Some(user) = self.get_user_entity(user_id) {
+            deviation.author = Nullable::Value(DeviationAuthor::Embedded(user.clone()));
+        }
+    }
+
+    /// Take a deviation's extended info by id, if it exists
+    pub fn take_deviation_extended_by_id(&mut self, id: DeviationId) -> Option<DeviationExtended> {
         self.entities
             .as_mut()?
-            .deviation
-            .remove(key_buffer.format(id))
+            .deviation_extended
+            .as_mut()?
+            .remove(&id)
     }
 
     /// Get the current folder id, if in a gallery.
-    pub fn get_current_folder_id(&self) -> Option<i64> {
+    pub fn get_current_folder_id(&self) -> Option<FolderId> {
         Some(self.gallection_section.as_ref()?.selected_folder_id)
     }
 
     /// Get a stream for folder post ids, by folder id.
     ///
     /// This will return the deviation ids for the current folder.
-    pub fn get_folder_deviations_stream(&self, folder_id: i64) -> Option<&WithOffsetStream> {
+    pub fn get_folder_deviations_stream(&self, folder_id: FolderId) -> Option<&WithOffsetStream> {
         let key = format!("folder-deviations-gallery-{folder_id}");
 
         self.streams
@@ -163,26 +315,28 @@ impl ScrapedWebPageInfo {
     }
 
     /// Get a gallery folder entity by id
-    pub fn get_gallery_folder_entity(&self, folder_id: i64) -> Option<&GalleryFolder> {
+    pub fn get_gallery_folder_entity(&self, folder_id: FolderId) -> Option<&GalleryFolder> {
         self.entities
             .as_ref()?
             .gallery_folder
             .as_ref()?
-            .get(itoa::Buffer::new().format(folder_id))
+            .get(&folder_id)
     }
 
     /// Get a user entity by id
-    pub fn get_user_entity(&self, user_id: u64) -> Option<&User> {
-        self.entities
-            .as_ref()?
-            .user
-            .as_ref()?
-            .get(itoa::Buffer::new().format(user_id))
+    pub fn get_user_entity(&self, user_id: UserId) -> Option<&User> {
+        self.entities.as_ref()?.user.as_ref()?.get(&user_id)
+    }
+
+    /// Take a user entity by id
+    pub fn take_user_entity(&mut self, user_id: UserId) -> Option<User> {
+        self.entities.as_mut()?.user.as_mut()?.remove(&user_id)
     }
 }
 
 /// ?
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Config {
     /// The page's csrf token
     #[serde(rename = "csrfToken")]
@@ -195,20 +349,34 @@ pub struct Config {
 
 /// Page entities, like deviations, folders, and users.
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Entities {
     /// Deviations
-    pub deviation: HashMap<String, Deviation>,
+    pub deviation: IdMap<DeviationId, Deviation>,
 
     /// Extended Deviation Info
-    #[serde(rename = "deviationExtended")]
-    pub deviation_extended: Option<HashMap<String, DeviationExtended>>,
+    #[serde(rename = "deviationExtended", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub deviation_extended: Nullable<IdMap<DeviationId, DeviationExtended>>,
 
     /// Gallery folders
-    #[serde(rename = "galleryFolder")]
-    pub gallery_folder: Option<HashMap<String, GalleryFolder>>,
+    #[serde(rename = "galleryFolder", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub gallery_folder: Nullable<IdMap<FolderId, GalleryFolder>>,
 
     /// Users
-    pub user: Option<HashMap<String, User>>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub user: Nullable<IdMap<UserId, User>>,
 
     /// Unknown data
     #[serde(flatten)]
@@ -217,20 +385,241 @@ pub struct Entities {
 
 /// Extended Info about a deviation
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct DeviationExtended {
     /// Download info
-    pub download: Option<Download>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub download: Nullable<Download>,
 
     /// HTML description
-    pub description: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub description: Nullable<String>,
 
     /// Other media for this deviation
-    #[serde(rename = "additionalMedia")]
-    pub additional_media: Option<Vec<AdditionalMedia>>,
+    #[serde(rename = "additionalMedia", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub additional_media: Nullable<Vec<AdditionalMedia>>,
 
     /// The id of the deviation this belongs to.
     #[serde(rename = "parentDeviationEntityId")]
-    pub parent_deviation_entity_id: u64,
+    pub parent_deviation_entity_id: DeviationId,
+
+    /// Tags
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub tags: Nullable<Lenient<Vec<Tag>>>,
+
+    /// Subject tags, like "dragon" or "landscape"
+    #[serde(rename = "subjectTags", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub subject_tags: Nullable<Lenient<Vec<Tag>>>,
+
+    /// The category path, like "digitalart/paintings/fantasy"
+    #[serde(rename = "categoryPath", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub category_path: Nullable<Lenient<String>>,
+
+    /// Stats
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stats: Nullable<Lenient<DeviationStats>>,
+
+    /// The license, like "none" or a Creative Commons license name
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub license: Nullable<Lenient<String>>,
+
+    /// The originality, like "original" or "fan art"
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub originality: Nullable<Lenient<String>>,
+
+    /// Camera info for photos
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub camera: Nullable<Lenient<Camera>>,
+
+    /// EXIF info for photos, which some pages send instead of `camera`
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub exif: Nullable<Lenient<Camera>>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// A deviation tag
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct Tag {
+    /// The tag name
+    pub name: String,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// Deviation stats
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct DeviationStats {
+    /// The number of views
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub views: Nullable<Lenient<u64>>,
+
+    /// The number of views today
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub today: Nullable<Lenient<u64>>,
+
+    /// The number of favourites
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub favourites: Nullable<Lenient<u64>>,
+
+    /// The number of comments
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub comments: Nullable<Lenient<u64>>,
+
+    /// The number of downloads
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub downloads: Nullable<Lenient<u64>>,
+
+    /// The number of shares
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub shares: Nullable<Lenient<u64>>,
+
+    /// The number of groups this was submitted to
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub groups: Nullable<Lenient<u64>>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// Camera and EXIF info
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct Camera {
+    /// The camera make
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub make: Nullable<Lenient<String>>,
+
+    /// The camera model
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub model: Nullable<Lenient<String>>,
+
+    /// The shutter speed, like "1/250 second"
+    #[serde(rename = "shutterSpeed", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub shutter_speed: Nullable<Lenient<String>>,
+
+    /// The aperture, like "F/5.6"
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub aperture: Nullable<Lenient<String>>,
+
+    /// The focal length, like "50 mm"
+    #[serde(rename = "focalLength", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub focal_length: Nullable<Lenient<String>>,
+
+    /// The ISO speed
+    #[serde(rename = "isoSpeed", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub iso_speed: Nullable<Lenient<String>>,
+
+    /// When the photo was taken, as written by the camera
+    #[serde(rename = "dateTaken", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub date_taken: Nullable<Lenient<String>>,
 
     /// Unknown data
     #[serde(flatten)]
@@ -238,6 +627,20 @@ pub struct DeviationExtended {
 }
 
 impl DeviationExtended {
+    /// Get the camera info, from either `camera` or `exif`.
+    pub fn camera_info(&self) -> Option<&Camera> {
+        self.camera.typed().or_else(|| self.exif.typed())
+    }
+
+    /// Get the categories from the category path, from most to least general.
+    pub fn categories(&self) -> impl Iterator<Item = &str> {
+        self.category_path
+            .typed()
+            .map_or("", String::as_str)
+            .split('/')
+            .filter(|category| !category.is_empty())
+    }
+
     /// Check if additional media is downloadable.
     ///
     /// Older additionalMedia deviation images can be downloaded.
@@ -253,7 +656,7 @@ impl DeviationExtended {
 
         // Old deviations can always be downloaded.
         // ID determined experimentally.
-        if self.parent_deviation_entity_id < 1184619292 {
+        if self.parent_deviation_entity_id < DeviationId(1184619292) {
             return true;
         }
 
@@ -261,24 +664,25 @@ impl DeviationExtended {
         // If present, we can download.
         additional_media
             .iter()
-            .all(|entry| entry.media.token.len() > 1)
+            .all(|entry| entry.media.tokens().len() > 1)
     }
 }
 
 /// A gallery folder
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct GalleryFolder {
     /// The folder id.
     ///
     /// For some reason, this can be -1 sometimes.
     #[serde(rename = "folderId")]
-    pub folder_id: i64,
+    pub folder_id: FolderId,
 
     /// The name of the folder
     pub name: String,
 
     /// The user id of the owner of the folder
-    pub owner: u64,
+    pub owner: UserId,
 
     /// Unknown data
     #[serde(flatten)]
@@ -286,21 +690,31 @@ pub struct GalleryFolder {
 }
 
 /// A user
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct User {
     /// The user id
     #[serde(rename = "userId")]
-    pub user_id: u64,
+    pub user_id: UserId,
 
     /// The user name
     pub username: String,
 
+    /// The account type, like "regular" or "admin"
+    #[serde(rename = "type", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub kind: Nullable<String>,
+
     /// Unknown data
     #[serde(flatten)]
     pub unknown: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Download {
     /// The file size
     pub filesize: u64,
@@ -324,6 +738,7 @@ pub struct Download {
 }
 
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct AdditionalMedia {
     /// Media info
     pub media: Media,
@@ -335,10 +750,15 @@ pub struct AdditionalMedia {
 
 /// ?
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct DuperBrowse {
     /// ?
-    #[serde(rename = "rootStream")]
-    pub root_stream: Option<RootStream>,
+    #[serde(rename = "rootStream", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub root_stream: Nullable<RootStream>,
 
     /// Unknown data
     #[serde(flatten)]
@@ -347,6 +767,7 @@ pub struct DuperBrowse {
 
 /// ?
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct RootStream {
     /// The id of the current deviation. This is either a number or string.
     #[serde(rename = "currentOpenItem")]
@@ -359,11 +780,83 @@ pub struct RootStream {
 
 /// ?
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct PublicSession {
     /// Whether the user is logged in
     #[serde(rename = "isLoggedIn")]
     pub is_logged_in: bool,
 
+    /// The current user.
+    ///
+    /// This is an anonymous user with an id of 0 if not logged in.
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub user: Nullable<User>,
+
+    /// Whether mature content is shown
+    #[serde(rename = "showMatureContent", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub show_mature_content: Nullable<bool>,
+
+    /// Whether the user has a core membership
+    #[serde(rename = "hasCoreMembership", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub has_core_membership: Nullable<bool>,
+
+    /// Whether the user has verified their email
+    #[serde(rename = "isVerified", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub is_verified: Nullable<bool>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// The state of the login form
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct LoginState {
+    /// Whether a captcha must be solved
+    #[serde(rename = "showCaptcha")]
+    pub show_captcha: bool,
+
+    /// Whether the username was rejected
+    #[serde(rename = "usernameInvalid")]
+    pub username_invalid: bool,
+
+    /// Whether the password was rejected
+    #[serde(rename = "passwordInvalid")]
+    pub password_invalid: bool,
+
+    /// An error message, or an empty string
+    #[serde(rename = "generalError")]
+    pub general_error: String,
+
+    /// The kind of error, or an empty string
+    #[serde(rename = "errorType")]
+    pub error_type: String,
+
+    /// Whether a 2FA verification code was rejected
+    #[serde(rename = "tfaVerificationCodeInvalid")]
+    pub tfa_verification_code_invalid: bool,
+
+    /// Whether a 2FA recovery code was rejected
+    #[serde(rename = "tfaRecoveryCodeInvalid")]
+    pub tfa_recovery_code_invalid: bool,
+
     /// Unknown data
     #[serde(flatten)]
     pub unknown: HashMap<String, serde_json::Value>,
@@ -371,10 +864,15 @@ pub struct PublicSession {
 
 /// The streams field
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct Streams {
     /// Search results appear here
-    #[serde(rename = "@@BROWSE_PAGE_STREAM")]
-    pub browse_page_stream: Option<BrowsePageStream>,
+    #[serde(rename = "@@BROWSE_PAGE_STREAM", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub browse_page_stream: Nullable<BrowsePageStream>,
 
     /// Extra data.
     ///
@@ -383,13 +881,26 @@ pub struct Streams {
     pub streams: HashMap<String, Stream>,
 }
 
-/// ?
+/// A stream of items, like a gallery folder, comments or search results.
+///
+/// DeviantArt pages streams in different ways.
+/// All of them share the methods here, which describe the loaded items and how to get more.
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 #[serde(tag = "streamType")]
 pub enum Stream {
+    /// A stream paged by offset
     #[serde(rename = "WITH_OFFSET")]
     WithOffset(WithOffsetStream),
 
+    /// A stream paged by an opaque cursor
+    #[serde(rename = "WITH_CURSOR")]
+    WithCursor(WithCursorStream),
+
+    /// A stream paged by page number
+    #[serde(rename = "WITH_PAGES")]
+    WithPages(WithPagesStream),
+
     #[serde(untagged)]
     Unknown(serde_json::Value),
 }
@@ -402,13 +913,132 @@ impl Stream {
             _ => None,
         }
     }
+
+    /// Get this as a WithCursor stream.
+    pub fn as_with_cursor_stream(&self) -> Option<&WithCursorStream> {
+        match self {
+            Self::WithCursor(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    /// Get this as a WithPages stream.
+    pub fn as_with_pages_stream(&self) -> Option<&WithPagesStream> {
+        match self {
+            Self::WithPages(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    /// Get the stream id, if this is a known stream type.
+    pub fn stream_id(&self) -> Option<&str> {
+        match self {
+            Self::WithOffset(stream) => stream.stream_id.as_deref(),
+            Self::WithCursor(stream) => stream.stream_id.as_deref(),
+            Self::WithPages(stream) => stream.stream_id.as_deref(),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Get the number of loaded items, if this is a known stream type.
+    pub fn items_len(&self) -> Option<usize> {
+        match self {
+            Self::WithOffset(stream) => Some(stream.items.len()),
+            Self::WithCursor(stream) => Some(stream.items.len()),
+            Self::WithPages(stream) => Some(stream.items.len()),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Returns `true` if there are more items to fetch.
+    ///
+    /// This is `false` for unknown stream types.
+    pub fn has_more(&self) -> bool {
+        match self {
+            Self::WithOffset(stream) => stream.has_more,
+            Self::WithCursor(stream) => stream.has_more,
+            Self::WithPages(stream) => stream.has_more,
+            Self::Unknown(_) => false,
+        }
+    }
+
+    /// Get the number of items to fetch at a time, if this is a known stream type.
+    pub fn items_per_fetch(&self) -> Option<u64> {
+        match self {
+            Self::WithOffset(stream) => Some(stream.items_per_fetch.into()),
+            Self::WithCursor(stream) => Some(stream.items_per_fetch),
+            Self::WithPages(stream) => Some(stream.items_per_fetch),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Get the params for fetching more items.
+    pub fn stream_params(&self) -> Option<&ItemStreamParams> {
+        match self {
+            Self::WithOffset(stream) => stream.stream_params.as_ref(),
+            Self::WithCursor(stream) => stream.stream_params.as_ref(),
+            Self::WithPages(stream) => stream.stream_params.as_ref(),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Get the name of the page action that fetches more items.
+    pub fn fetch_next_callback(&self) -> Option<&str> {
+        match self {
+            Self::WithOffset(stream) => stream.fetch_next_callback.as_deref(),
+            Self::WithCursor(stream) => stream.fetch_next_callback.as_deref(),
+            Self::WithPages(stream) => stream.fetch_next_callback.as_deref(),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Get the position of the next page, if there are more items.
+    pub fn next_position(&self) -> Option<StreamPosition> {
+        if !self.has_more() {
+            return None;
+        }
+
+        match self {
+            Self::WithOffset(stream) => {
+                let initial_offset = stream
+                    .stream_params
+                    .as_ref()
+                    .and_then(|stream_params| stream_params.initial_offset.as_ref().copied())
+                    .unwrap_or(0);
+                Some(StreamPosition::Offset(
+                    initial_offset + u64::try_from(stream.items.len()).ok()?,
+                ))
+            }
+            Self::WithCursor(stream) => {
+                Some(StreamPosition::Cursor(stream.cursor.as_ref()?.clone()))
+            }
+            Self::WithPages(stream) => {
+                Some(StreamPosition::Page(stream.current_page.as_ref()? + 1))
+            }
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// Where to continue a stream from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamPosition {
+    /// An item offset
+    Offset(u64),
+
+    /// An opaque cursor
+    Cursor(String),
+
+    /// A page number
+    Page(u64),
 }
 
 /// ?
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct WithOffsetStream {
     /// Items in the stream?
-    pub items: Vec<u64>,
+    pub items: Vec<DeviationId>,
 
     /// The # of items per fetch?
     #[serde(rename = "itemsPerFetch")]
@@ -422,6 +1052,154 @@ pub struct WithOffsetStream {
     #[serde(rename = "hasLess")]
     pub has_less: bool,
 
+    /// The stream id
+    #[serde(rename = "streamId", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_id: Nullable<String>,
+
+    /// Stream Params
+    #[serde(rename = "streamParams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_params: Nullable<ItemStreamParams>,
+
+    /// The name of the page action that fetches more items
+    #[serde(rename = "fetchNextCallback", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub fetch_next_callback: Nullable<String>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// A stream paged by an opaque cursor, like comments.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct WithCursorStream {
+    /// Items in the stream.
+    ///
+    /// Depending on the stream, these are ids or whole objects.
+    pub items: Vec<serde_json::Value>,
+
+    /// The # of items per fetch
+    #[serde(rename = "itemsPerFetch")]
+    pub items_per_fetch: u64,
+
+    /// Has more entries?
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+
+    /// ?
+    #[serde(rename = "hasLess")]
+    pub has_less: bool,
+
+    /// The cursor for the next page
+    #[serde(alias = "nextCursor", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub cursor: Nullable<String>,
+
+    /// The stream id
+    #[serde(rename = "streamId", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_id: Nullable<String>,
+
+    /// Stream Params
+    #[serde(rename = "streamParams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_params: Nullable<ItemStreamParams>,
+
+    /// The name of the page action that fetches more items
+    #[serde(rename = "fetchNextCallback", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub fetch_next_callback: Nullable<String>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+/// A stream paged by page number.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct WithPagesStream {
+    /// Items in the stream.
+    ///
+    /// Depending on the stream, these are ids or whole objects.
+    pub items: Vec<serde_json::Value>,
+
+    /// The # of items per fetch
+    #[serde(rename = "itemsPerFetch")]
+    pub items_per_fetch: u64,
+
+    /// Has more entries?
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+
+    /// ?
+    #[serde(rename = "hasLess")]
+    pub has_less: bool,
+
+    /// The current page number
+    #[serde(rename = "currentPage", alias = "page", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub current_page: Nullable<u64>,
+
+    /// The total number of pages
+    #[serde(rename = "totalPages", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub total_pages: Nullable<u64>,
+
+    /// The stream id
+    #[serde(rename = "streamId", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_id: Nullable<String>,
+
+    /// Stream Params
+    #[serde(rename = "streamParams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub stream_params: Nullable<ItemStreamParams>,
+
+    /// The name of the page action that fetches more items
+    #[serde(rename = "fetchNextCallback", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub fetch_next_callback: Nullable<String>,
+
     /// Unknown data
     #[serde(flatten)]
     pub unknown: HashMap<String, serde_json::Value>,
@@ -429,6 +1207,7 @@ pub struct WithOffsetStream {
 
 /// Search results appear here
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct BrowsePageStream {
     /// The cursor
     pub cursor: String,
@@ -441,12 +1220,8 @@ pub struct BrowsePageStream {
     #[serde(rename = "hasMore")]
     pub has_more: bool,
 
-    /// Deviation ids?
-    ///
-    /// Usually, these are integers representing deviation ids.
-    /// In some cases, these are strings of the format "xx-nnnnn",
-    /// where the "xx" part is unknown and the "nnnnn" part is a deviation id.
-    pub items: Vec<serde_json::Value>,
+    /// The items on this page, usually deviations.
+    pub items: Vec<BrowseStreamItem>,
 
     /// The # of items per page
     #[serde(rename = "itemsPerFetch")]
@@ -473,8 +1248,121 @@ pub struct BrowsePageStream {
     pub unknown: HashMap<String, serde_json::Value>,
 }
 
+/// An item in a [`BrowsePageStream`].
+///
+/// Usually, these are integers representing deviation ids.
+/// In some cases, these are strings of the format "xx-nnnnn",
+/// where the "xx" part is the entity type id and the "nnnnn" part is the entity id.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[serde(from = "serde_json::Value")]
+#[cfg_attr(feature = "serde-serialize", serde(into = "serde_json::Value"))]
+pub enum BrowseStreamItem {
+    /// A plain deviation id
+    Deviation(DeviationId),
+
+    /// A "typeId-id" string
+    Prefixed {
+        /// The kind of entity, decoded from the type id
+        kind: EntityKind,
+
+        /// The entity id
+        id: u64,
+    },
+
+    /// An item in an unknown format
+    Unknown(serde_json::Value),
+}
+
+impl BrowseStreamItem {
+    /// Get the deviation id, if this item is a deviation.
+    pub fn deviation_id(&self) -> Option<DeviationId> {
+        match self {
+            Self::Deviation(id) => Some(*id),
+            Self::Prefixed {
+                kind: EntityKind::Deviation,
+                id,
+            } => Some(DeviationId(*id)),
+            Self::Prefixed { .. } | Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Value> for BrowseStreamItem {
+    fn from(value: serde_json::Value) -> Self {
+        let prefixed = value.as_str().and_then(|value| {
+            let (type_id, id) = value.split_once('-')?;
+            let type_id: u32 = type_id.parse().ok()?;
+            let id: u64 = id.parse().ok()?;
+
+            // Keep non-canonical strings, like "01-5", as they are so they serialize unchanged.
+            if format!("{type_id}-{id}") != value {
+                return None;
+            }
+
+            Some(Self::Prefixed {
+                kind: EntityKind::from_type_id(type_id),
+                id,
+            })
+        });
+
+        match value.as_u64() {
+            Some(id) => Self::Deviation(DeviationId(id)),
+            None => prefixed.unwrap_or(Self::Unknown(value)),
+        }
+    }
+}
+
+impl From<BrowseStreamItem> for serde_json::Value {
+    fn from(item: BrowseStreamItem) -> Self {
+        match item {
+            BrowseStreamItem::Deviation(id) => id.0.into(),
+            BrowseStreamItem::Prefixed { kind, id } => format!("{}-{id}", kind.type_id()).into(),
+            BrowseStreamItem::Unknown(value) => value,
+        }
+    }
+}
+
+/// The kind of an entity, from its numeric type id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EntityKind {
+    /// A deviation, type id 1
+    Deviation,
+
+    /// An unknown type id
+    Other(u32),
+}
+
+impl EntityKind {
+    /// Get the kind for a type id.
+    pub fn from_type_id(type_id: u32) -> Self {
+        match type_id {
+            1 => Self::Deviation,
+            type_id => Self::Other(type_id),
+        }
+    }
+
+    /// Get the type id.
+    pub fn type_id(self) -> u32 {
+        match self {
+            Self::Deviation => 1,
+            Self::Other(type_id) => type_id,
+        }
+    }
+}
+
+impl BrowsePageStream {
+    /// Get the position of the next page, if there are more items.
+    pub fn next_position(&self) -> Option<StreamPosition> {
+        self.has_more
+            .then(|| StreamPosition::Cursor(self.cursor.clone()))
+    }
+}
+
 /// Stream params
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct StreamParams {
     /// Request params
     #[serde(rename = "requestParams")]
@@ -497,12 +1385,156 @@ pub struct StreamParams {
     pub unknown: HashMap<String, serde_json::Value>,
 }
 
+/// The params for fetching more items of a [`Stream`].
+///
+/// Unlike [`StreamParams`], every field may be missing,
+/// as streams that are only continued by page scripts, like comments, have few params.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct ItemStreamParams {
+    /// Request params
+    #[serde(rename = "requestParams", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub request_params: Nullable<HashMap<String, serde_json::Value>>,
+
+    /// ?
+    #[serde(rename = "itemType", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub item_type: Nullable<String>,
+
+    /// The path of the api endpoint that returns more items
+    #[serde(rename = "requestEndpoint", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub request_endpoint: Nullable<String>,
+
+    /// ?
+    #[serde(rename = "initialOffset", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub initial_offset: Nullable<u64>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+impl ItemStreamParams {
+    /// Get the request params as query pairs.
+    ///
+    /// Null params are skipped, and params that are not strings are written as json.
+    pub fn query_pairs(&self) -> Vec<(&str, Cow<'_, str>)> {
+        self.request_params
+            .as_ref()
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, value)| match value {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(value) => {
+                    Some((key.as_str(), Cow::Borrowed(value.as_str())))
+                }
+                value => Some((key.as_str(), Cow::Owned(value.to_string()))),
+            })
+            .collect()
+    }
+}
+
+/// A stream that can be continued with [`Client::fetch_next_stream_page`](crate::Client::fetch_next_stream_page).
+pub trait ContinuableStream {
+    /// Get the position of the next page, if there are more items.
+    fn next_position(&self) -> Option<StreamPosition>;
+
+    /// Get the path of the api endpoint that returns more items, if there is one.
+    fn request_endpoint(&self) -> Option<&str>;
+
+    /// Get the query params for the request endpoint.
+    fn request_params(&self) -> Vec<(&str, Cow<'_, str>)>;
+
+    /// Get the number of items to fetch at a time, if it is known.
+    fn items_per_fetch(&self) -> Option<u64>;
+
+    /// Get the stream id, if it is known.
+    fn stream_id(&self) -> Option<&str>;
+
+    /// Get the name of the page action that fetches more items, if it is known.
+    fn fetch_next_callback(&self) -> Option<&str>;
+}
+
+impl ContinuableStream for Stream {
+    fn next_position(&self) -> Option<StreamPosition> {
+        Stream::next_position(self)
+    }
+
+    fn request_endpoint(&self) -> Option<&str> {
+        self.stream_params()?.request_endpoint.as_deref()
+    }
+
+    fn request_params(&self) -> Vec<(&str, Cow<'_, str>)> {
+        self.stream_params()
+            .map(ItemStreamParams::query_pairs)
+            .unwrap_or_default()
+    }
+
+    fn items_per_fetch(&self) -> Option<u64> {
+        Stream::items_per_fetch(self)
+    }
+
+    fn stream_id(&self) -> Option<&str> {
+        Stream::stream_id(self)
+    }
+
+    fn fetch_next_callback(&self) -> Option<&str> {
+        Stream::fetch_next_callback(self)
+    }
+}
+
+impl ContinuableStream for BrowsePageStream {
+    fn next_position(&self) -> Option<StreamPosition> {
+        BrowsePageStream::next_position(self)
+    }
+
+    fn request_endpoint(&self) -> Option<&str> {
+        Some(&self.stream_params.request_endpoint)
+    }
+
+    fn request_params(&self) -> Vec<(&str, Cow<'_, str>)> {
+        self.stream_params
+            .request_params
+            .iter()
+            .map(|(key, value)| (key.as_str(), Cow::Borrowed(value.as_str())))
+            .collect()
+    }
+
+    fn items_per_fetch(&self) -> Option<u64> {
+        Some(self.items_per_fetch)
+    }
+
+    fn stream_id(&self) -> Option<&str> {
+        Some(&self.stream_id)
+    }
+
+    fn fetch_next_callback(&self) -> Option<&str> {
+        Some(&self.fetch_next_callback)
+    }
+}
+
 /// Gallery selection info
 #[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
 pub struct GallectionSection {
     /// The id of the selected folder
     #[serde(rename = "selectedFolderId")]
-    pub selected_folder_id: i64,
+    pub selected_folder_id: FolderId,
 
     /// Unknown data
     #[serde(flatten)]
@@ -512,6 +1544,9 @@ pub struct GallectionSection {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::DeviationKind;
+    use crate::types::MediaTypeKind;
+    use crate::types::Timestamp;
 
     const SCRAPED_WEBPAGE: &str = include_str!("../../test_data/scraped_webpage.json");
     const LOGIN_WEBPAGE: &str = include_str!("../../test_data/login_webpage.json");
@@ -524,14 +1559,404 @@ mod test {
             scraped_webpage_info
                 .get_current_deviation_id()
                 .expect("missing current deviation id"),
-            119577071
+            DeviationId(119577071)
         );
         // dbg!(scraped_deviation_info.entities.deviation);
     }
 
+    #[test]
+    fn parse_kinds() {
+        let scraped_webpage_info: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let deviation = scraped_webpage_info
+            .get_current_deviation()
+            .expect("missing current deviation");
+        assert!(deviation.kind == DeviationKind::Image);
+        assert!(
+            deviation
+                .media
+                .types
+                .iter()
+                .any(|media_type| media_type.kind == MediaTypeKind::Thumb200H)
+        );
+        assert!(deviation.media.get_fullview_media_type().is_some());
+
+        let kinds: Vec<DeviationKind> =
+            serde_json::from_str(r#"["journal", "pdf", "hologram"]"#).expect("failed to parse");
+        assert_eq!(
+            kinds,
+            [
+                DeviationKind::Journal,
+                DeviationKind::Pdf,
+                DeviationKind::from(String::from("hologram"))
+            ]
+        );
+        assert!(kinds[2].as_str() == "hologram");
+        assert!(DeviationKind::from(String::from("image")) == DeviationKind::Image);
+
+        let kinds: Vec<MediaTypeKind> =
+            serde_json::from_str(r#"["1080p", "hologram"]"#).expect("failed to parse");
+        assert!(kinds[0] == MediaTypeKind::Film1080p);
+        assert!(kinds[0].as_str() == "1080p");
+        assert!(matches!(&kinds[1], MediaTypeKind::Other(kind) if kind.as_str() == "hologram"));
+    }
+
+    #[test]
+    fn parse_deviation_extended_metadata() {
+        let scraped_webpage_info: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let deviation = scraped_webpage_info
+            .get_current_deviation()
+            .expect("missing current deviation");
+        let published_time = deviation
+            .published_time
+            .typed()
+            .and_then(Timestamp::datetime)
+            .expect("missing published time");
+        assert!(published_time.unix_timestamp() == 1239993453);
+        assert!(published_time.offset().whole_hours() == -7);
+
+        let deviation_extended = scraped_webpage_info
+            .get_current_deviation_extended()
+            .expect("missing deviation extended");
+        let stats = deviation_extended.stats.typed().expect("missing stats");
+        assert!(stats.views.typed() == Some(&13376));
+        assert!(stats.downloads.typed() == Some(&1903));
+        assert!(deviation_extended.license.typed().map(String::as_str) == Some("none"));
+
+        let deviation_extended: DeviationExtended = serde_json::from_value(serde_json::json!({
+            "parentDeviationEntityId": 1,
+            "categoryPath": "photography/nature/landscapes",
+            "tags": [{ "name": "sunset" }],
+            "subjectTags": [{ "name": "mountains" }],
+            "originality": "original",
+            "stats": { "views": 10, "favourites": 2, "comments": 1 },
+            "exif": { "make": "Canon", "model": "EOS 5D", "isoSpeed": "100" },
+        }))
+        .expect("failed to parse deviation extended");
+        assert!(
+            deviation_extended
+                .categories()
+                .eq(["photography", "nature", "landscapes"])
+        );
+        assert!(deviation_extended.tags.typed().expect("missing tags")[0].name == "sunset");
+        assert!(
+            deviation_extended
+                .subject_tags
+                .typed()
+                .expect("missing subject tags")[0]
+                .name
+                == "mountains"
+        );
+        assert!(deviation_extended.originality.typed().map(String::as_str) == Some("original"));
+        let stats = deviation_extended.stats.typed().expect("missing stats");
+        assert!(stats.favourites.typed() == Some(&2) && stats.comments.typed() == Some(&1));
+        let camera = deviation_extended.camera_info().expect("missing camera");
+        assert!(camera.make.typed().map(String::as_str) == Some("Canon"));
+        assert!(camera.iso_speed.typed().map(String::as_str) == Some("100"));
+
+        // Unexpected shapes should not fail the page, and should be kept as they were.
+        let deviation_extended: DeviationExtended = serde_json::from_value(serde_json::json!({
+            "parentDeviationEntityId": 1,
+            "categoryPath": ["photography", "nature"],
+            "tags": "sunset",
+            "subjectTags": ["mountains"],
+            "originality": { "isOriginal": true },
+            "stats": { "views": "1,024", "favourites": null, "comments": -1 },
+            "exif": { "isoSpeed": 100, "aperture": { "value": 5.6 } },
+        }))
+        .expect("failed to parse deviation extended");
+        assert!(deviation_extended.categories().next().is_none());
+        assert!(
+            deviation_extended
+                .category_path
+                .as_ref()
+                .and_then(Lenient::raw)
+                == Some(&serde_json::json!(["photography", "nature"]))
+        );
+        assert!(deviation_extended.tags.typed().is_none());
+        assert!(deviation_extended.subject_tags.typed().is_none());
+        assert!(deviation_extended.originality.typed().is_none());
+        let stats = deviation_extended.stats.typed().expect("missing stats");
+        assert!(stats.views.typed().is_none());
+        assert!(stats.views.as_ref().and_then(Lenient::count) == Some(1024));
+        assert!(stats.favourites.is_null());
+        assert!(stats.comments.as_ref().and_then(Lenient::raw) == Some(&serde_json::json!(-1)));
+        let camera = deviation_extended.camera_info().expect("missing camera");
+        assert!(camera.iso_speed.as_ref().and_then(Lenient::raw) == Some(&serde_json::json!(100)));
+        assert!(camera.aperture.typed().is_none());
+    }
+
+    #[test]
+    fn parse_browse_stream_items() {
+        let items: Vec<BrowseStreamItem> =
+            serde_json::from_str(r#"[123, "1-456", "4-789", "1-abc", "01-5", {"id": 1}]"#)
+                .expect("failed to parse items");
+        assert_eq!(
+            items,
+            [
+                BrowseStreamItem::Deviation(DeviationId(123)),
+                BrowseStreamItem::Prefixed {
+                    kind: EntityKind::Deviation,
+                    id: 456
+                },
+                BrowseStreamItem::Prefixed {
+                    kind: EntityKind::Other(4),
+                    id: 789
+                },
+                BrowseStreamItem::Unknown("1-abc".into()),
+                BrowseStreamItem::Unknown("01-5".into()),
+                BrowseStreamItem::Unknown(serde_json::json!({"id": 1})),
+            ]
+        );
+        let deviation_ids: Vec<_> = items
+            .iter()
+            .filter_map(|item| item.deviation_id())
+            .collect();
+        assert_eq!(deviation_ids, [DeviationId(123), DeviationId(456)]);
+    }
+
+    #[test]
+    fn parse_streams() {
+        let scraped_webpage_info: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let streams = &scraped_webpage_info
+            .streams
+            .as_ref()
+            .expect("missing streams")
+            .streams;
+
+        let comments = &streams["comments:1-119577071"];
+        assert!(comments.as_with_cursor_stream().is_some(), "{comments:?}");
+        assert!(comments.stream_id() == Some("comments:1-119577071"));
+        assert!(comments.items_len().is_some_and(|len| len > 0));
+        assert!(
+            comments.fetch_next_callback() == Some("@@CommentThread/THREAD_FETCH_NEXT"),
+            "{comments:?}"
+        );
+
+        let groups = &streams["NETWORKBAR_WATCHED_GROUPS"];
+        assert!(groups.has_more());
+        assert!(groups.items_per_fetch() == Some(50));
+
+        let stream: Stream = serde_json::from_value(serde_json::json!({
+            "streamType": "WITH_PAGES",
+            "items": [1, 2],
+            "itemsPerFetch": 2,
+            "hasMore": true,
+            "hasLess": false,
+            "currentPage": 1,
+        }))
+        .expect("failed to parse stream");
+        assert!(stream.next_position() == Some(StreamPosition::Page(2)));
+
+        let stream: Stream = serde_json::from_value(serde_json::json!({
+            "streamType": "WITH_OFFSET",
+            "items": [1, 2],
+            "itemsPerFetch": 2,
+            "hasMore": true,
+            "hasLess": false,
+            "streamParams": { "initialOffset": 10 },
+        }))
+        .expect("failed to parse stream");
+        assert!(stream.next_position() == Some(StreamPosition::Offset(12)));
+    }
+
+    #[test]
+    fn resolve_deviation_author() {
+        let mut scraped_webpage_info: ScrapedWebPageInfo =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse scraped webpage info");
+        let deviation = scraped_webpage_info
+            .get_deviation_by_id(DeviationId(17941730))
+            .expect("missing deviation");
+        assert!(matches!(
+            deviation.author,
+            Nullable::Value(DeviationAuthor::Id(UserId(375780)))
+        ));
+        assert!(deviation.author_username().is_none());
+        let author = scraped_webpage_info
+            .get_deviation_author(deviation)
+            .expect("missing author");
+        assert!(author.username == "lexidh");
+        assert!(scraped_webpage_info.get_deviation_author_username(deviation) == Some("lexidh"));
+
+        let deviation = scraped_webpage_info
+            .take_deviation_by_id(DeviationId(17941730))
+            .expect("missing deviation");
+        assert!(deviation.author_username() == Some("lexidh"));
+        let author = deviation
+            .author
+            .as_ref()
+            .and_then(DeviationAuthor::as_embedded)
+            .expect("missing embedded author");
+        assert!(author.user_id == UserId(375780));
+        assert!(author.unknown.contains_key("usericon"));
+        assert!(
+            scraped_webpage_info
+                .get_deviation_author(&deviation)
+                .is_some_and(|author| author.username == "lexidh")
+        );
+
+        let login_webpage: serde_json::Value =
+            serde_json::from_str(LOGIN_WEBPAGE).expect("failed to parse login webpage");
+        let deviation: Deviation = serde_json::from_value(login_webpage["cardDeviation"].clone())
+            .expect("failed to parse deviation");
+        assert!(deviation.author_username() == Some("endprocess83"));
+
+        // A partial embedded author does not fail the deviation.
+        let mut card_deviation = login_webpage["cardDeviation"].clone();
+        card_deviation["author"] = serde_json::json!({ "userId": 375780 });
+        let deviation: Deviation =
+            serde_json::from_value(card_deviation).expect("failed to parse deviation");
+        let author = deviation.author.as_ref().expect("missing author");
+        assert!(matches!(author, DeviationAuthor::Raw(_)));
+        assert!(author.user_id() == Some(UserId(375780)));
+        assert!(deviation.author_username().is_none());
+        assert!(
+            scraped_webpage_info
+                .get_deviation_author(&deviation)
+                .is_some_and(|author| author.username == "lexidh")
+        );
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn serialize_round_trip() {
+        for input in [SCRAPED_WEBPAGE, LOGIN_WEBPAGE] {
+            let expected: serde_json::Value =
+                serde_json::from_str(input).expect("failed to parse json");
+            let scraped_webpage_info: ScrapedWebPageInfo =
+                serde_json::from_str(input).expect("failed to parse scraped webpage info");
+            let serialized =
+                serde_json::to_value(&scraped_webpage_info).expect("failed to serialize");
+
+            assert!(serialized == expected);
+        }
+
+        use crate::types::StreamPageResponse;
+        use crate::types::lazy_scraped_webpage_info::LazyEntities;
+
+        // Values that are easy to lose, like explicit nulls and empty collections
+        fn assert_round_trip<T>(input: &str)
+        where
+            T: serde::de::DeserializeOwned + serde::Serialize,
+        {
+            let expected: serde_json::Value =
+                serde_json::from_str(input).expect("failed to parse json");
+            let value: T = serde_json::from_str(input).expect("failed to parse value");
+            let serialized = serde_json::to_value(&value).expect("failed to serialize");
+            assert!(serialized == expected);
+        }
+
+        assert_round_trip::<Media>(r#"{"token": [], "types": []}"#);
+        assert_round_trip::<Media>(r#"{"types": []}"#);
+        assert_round_trip::<Media>(r#"{"baseUri": null, "token": null, "types": []}"#);
+        assert_round_trip::<Vec<BrowseStreamItem>>(r#"[1, "1-5", "01-5"]"#);
+        assert_round_trip::<StreamPageResponse>(
+            r#"{"hasMore": false, "nextOffset": null, "nextCursor": null, "results": []}"#,
+        );
+        assert_round_trip::<StreamPageResponse>(r#"{"hasMore": false, "results": null}"#);
+        assert_round_trip::<StreamPageResponse>(r#"{"hasMore": false}"#);
+        assert_round_trip::<ItemStreamParams>(
+            r#"{"requestParams": {}, "itemType": null, "initialOffset": null}"#,
+        );
+        assert_round_trip::<ItemStreamParams>(r#"{"requestParams": null}"#);
+        assert_round_trip::<ItemStreamParams>("{}");
+        assert_round_trip::<LazyEntities>(r#"{"deviation": {}, "deviationExtended": null}"#);
+        assert_round_trip::<LazyEntities>("{}");
+        assert_round_trip::<Entities>(r#"{"deviation": {}, "galleryFolder": {}, "user": null}"#);
+        assert_round_trip::<Entities>(
+            r#"{"deviation": {"abc": {"partial": true}}, "user": {"-3": null}}"#,
+        );
+        assert_round_trip::<DeviationExtended>(
+            r#"{
+                "parentDeviationEntityId": 1,
+                "categoryPath": ["photography"],
+                "tags": "sunset",
+                "stats": {"views": "1,234", "comments": -1, "shares": null},
+                "exif": {"isoSpeed": 100, "make": true, "aperture": {"value": 5.6}},
+                "license": null
+            }"#,
+        );
+
+        let mut page: serde_json::Value =
+            serde_json::from_str(SCRAPED_WEBPAGE).expect("failed to parse json");
+        for deviation in page["@@entities"]["deviation"]
+            .as_object_mut()
+            .expect("missing deviations")
+            .values_mut()
+        {
+            deviation["publishedTime"] = serde_json::json!(1239993453);
+            assert_round_trip::<Deviation>(&deviation.to_string());
+        }
+    }
+
     #[test]
     fn parse_login_webpage() {
         let _scraped_webpage_info: ScrapedWebPageInfo =
             serde_json::from_str(LOGIN_WEBPAGE).expect("failed to parse scraped webpage info");
     }
+
+    #[test]
+    fn from_html_str() {
+        let literal = LOGIN_WEBPAGE.replace('\\', "\\\\").replace('"', "\\\"");
+        let page = format!(
+            "<html><script>window.__INITIAL_STATE__ = JSON.parse(\"{literal}\");</script><script>f(\"\");</script></html>"
+        );
+        let from_html: ScrapedWebPageInfo =
+            ScrapedWebPageInfo::from_html_str(&page).expect("failed to parse html");
+        let from_json: ScrapedWebPageInfo =
+            serde_json::from_str(LOGIN_WEBPAGE).expect("failed to parse json");
+        assert_eq!(from_html.config.csrf_token, from_json.config.csrf_token);
+
+        let page = "<script>window.__INITIAL_STATE__ = JSON.parse(\"{\\\"a\\\":";
+        let error =
+            ScrapedWebPageInfo::from_html_str(page).expect_err("parsed an unterminated page");
+        assert!(
+            matches!(error, FromHtmlStrError::MissingInitialState),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn detect_interstitial_pages() {
+        let page = "<html><head><title>Just a moment...</title></head></html>";
+        let error = ScrapedWebPageInfo::from_html_str(page).expect_err("parsed a challenge page");
+        assert!(
+            matches!(error, FromHtmlStrError::Challenge { .. }),
+            "{error:?}"
+        );
+        assert_eq!(error.page(), Some(page));
+
+        let page = "<html><head><title>DeviantArt: Maintenance</title></head></html>";
+        let error = ScrapedWebPageInfo::from_html_str(page).expect_err("parsed a maintenance page");
+        assert!(
+            matches!(error, FromHtmlStrError::Maintenance { .. }),
+            "{error:?}"
+        );
+
+        let page = "<html><head><title>DeviantArt: Mature Content</title></head></html>";
+        let error =
+            ScrapedWebPageInfo::from_html_str(page).expect_err("parsed a mature content page");
+        assert!(
+            matches!(error, FromHtmlStrError::MatureContentGate { .. }),
+            "{error:?}"
+        );
+        assert_eq!(error.page(), Some(page));
+
+        // Phrases in a page without an initial state are not enough.
+        let page = "<html><head><title>Sunset</title></head><body>This deviation was deleted. Just a moment...</body></html>";
+        let error = ScrapedWebPageInfo::from_html_str(page).expect_err("parsed an empty page");
+        assert!(
+            matches!(error, FromHtmlStrError::MissingInitialState),
+            "{error:?}"
+        );
+
+        let error =
+            ScrapedWebPageInfo::from_html_str("<html></html>").expect_err("parsed an empty page");
+        assert!(
+            matches!(error, FromHtmlStrError::MissingInitialState),
+            "{error:?}"
+        );
+    }
 }
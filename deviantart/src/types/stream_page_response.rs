@@ -0,0 +1,70 @@
+use super::Nullable;
+use super::scraped_webpage_info::StreamPosition;
+use std::collections::HashMap;
+
+/// A page of stream items from a stream's request endpoint.
+#[derive(Debug, serde::Deserialize)]
+#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+pub struct StreamPageResponse {
+    /// Whether this has more
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+
+    /// The next offset, for offset streams
+    #[serde(rename = "nextOffset", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub next_offset: Nullable<u64>,
+
+    /// The next cursor, for cursor streams
+    #[serde(rename = "nextCursor", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub next_cursor: Nullable<String>,
+
+    /// The next page, for paged streams
+    #[serde(rename = "nextPage", default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub next_page: Nullable<u64>,
+
+    /// The items.
+    ///
+    /// Depending on the stream, these are ids or whole objects.
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(skip_serializing_if = "Nullable::is_missing")
+    )]
+    pub results: Nullable<Vec<serde_json::Value>>,
+
+    /// Unknown data
+    #[serde(flatten)]
+    pub unknown: HashMap<String, serde_json::Value>,
+}
+
+impl StreamPageResponse {
+    /// Get the position of the next page, if there are more items.
+    pub fn next_position(&self) -> Option<StreamPosition> {
+        if !self.has_more {
+            return None;
+        }
+
+        if let Some(next_cursor) = self.next_cursor.as_ref() {
+            return Some(StreamPosition::Cursor(next_cursor.clone()));
+        }
+        if let Nullable::Value(next_page) = self.next_page {
+            return Some(StreamPosition::Page(next_page));
+        }
+        self.next_offset
+            .as_ref()
+            .copied()
+            .map(StreamPosition::Offset)
+    }
+}
@@ -0,0 +1,121 @@
+use time::OffsetDateTime;
+use time::format_description::BorrowedFormatItem;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+
+/// The timestamp format DeviantArt uses, which has no colon in the offset.
+const FORMAT: &[BorrowedFormatItem<'_>] = format_description!(
+    "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory][offset_minute]"
+);
+
+/// A timestamp.
+///
+/// A string is only parsed if formatting the result gives the same string back,
+/// so this always serializes back to what was read.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Timestamp {
+    /// A timestamp in DeviantArt's format, which has no colon in the offset
+    DeviantArt(OffsetDateTime),
+
+    /// An RFC 3339 timestamp
+    Rfc3339(OffsetDateTime),
+
+    /// A string that could not be parsed as a timestamp
+    Raw(String),
+}
+
+impl Timestamp {
+    /// Get the date and time, if the timestamp could be parsed.
+    pub fn datetime(&self) -> Option<OffsetDateTime> {
+        match self {
+            Self::DeviantArt(datetime) | Self::Rfc3339(datetime) => Some(*datetime),
+            Self::Raw(_) => None,
+        }
+    }
+
+    /// Format the timestamp as it was read.
+    pub fn to_string_lossless(&self) -> Result<String, time::error::Format> {
+        match self {
+            Self::DeviantArt(datetime) => datetime.format(FORMAT),
+            Self::Rfc3339(datetime) => datetime.format(&Rfc3339),
+            Self::Raw(raw) => Ok(raw.clone()),
+        }
+    }
+}
+
+impl From<String> for Timestamp {
+    fn from(input: String) -> Self {
+        let parsed = [
+            OffsetDateTime::parse(&input, FORMAT).map(Self::DeviantArt),
+            OffsetDateTime::parse(&input, &Rfc3339).map(Self::Rfc3339),
+        ];
+        parsed
+            .into_iter()
+            .flatten()
+            .find(|timestamp| {
+                timestamp
+                    .to_string_lossless()
+                    .is_ok_and(|formatted| formatted == input)
+            })
+            .unwrap_or(Self::Raw(input))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let input: String = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Self::from(input))
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl serde::Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let formatted = self
+            .to_string_lossless()
+            .map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&formatted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_timestamps() {
+        let timestamp = Timestamp::from(String::from("2009-04-17T11:37:33-0700"));
+        assert!(matches!(timestamp, Timestamp::DeviantArt(_)));
+        let datetime = timestamp.datetime().expect("failed to parse");
+        assert!(datetime.unix_timestamp() == 1239993453);
+        assert!(datetime.offset().whole_hours() == -7);
+
+        let timestamp = Timestamp::from(String::from("2009-04-17T18:37:33Z"));
+        assert!(matches!(timestamp, Timestamp::Rfc3339(_)));
+        assert!(timestamp.datetime().map(OffsetDateTime::unix_timestamp) == Some(1239993453));
+
+        let timestamp = Timestamp::from(String::from("yesterday"));
+        assert!(timestamp == Timestamp::Raw(String::from("yesterday")));
+        assert!(timestamp.datetime().is_none());
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn round_trip() {
+        for input in [
+            r#""2009-04-17T11:37:33-0700""#,
+            r#""2009-04-17T18:37:33Z""#,
+            r#""2009-04-17T18:37:33+00:00""#,
+            r#""yesterday""#,
+        ] {
+            let timestamp: Timestamp = serde_json::from_str(input).expect("failed to parse");
+            assert!(serde_json::to_string(&timestamp).expect("failed to serialize") == input);
+        }
+    }
+}